@@ -40,6 +40,20 @@ fn criterion_benchmark(c: &mut Criterion) {
             registry.get_component::<P>(black_box(entity)).unwrap();
         }, BatchSize::SmallInput)
     });
+    c.bench_function("has_component_lookup", |b|{
+        b.iter_batched(||{
+            *entities.choose(&mut thread_rng()).unwrap()
+        }, |entity| {
+            black_box(registry.has_component::<S>(black_box(entity)));
+        }, BatchSize::SmallInput)
+    });
+    c.bench_function("contains_fast_lookup", |b|{
+        b.iter_batched(||{
+            *entities.choose(&mut thread_rng()).unwrap()
+        }, |entity| {
+            black_box(registry.contains_fast::<S>(black_box(entity)));
+        }, BatchSize::SmallInput)
+    });
     c.bench_function("iterate_entities", |b|{
         b.iter(||{
             for entity in registry.iter_entities() {
@@ -56,6 +70,15 @@ fn criterion_benchmark(c: &mut Criterion) {
             }
         });
     });
+    c.bench_function("iterate_single_p_components", |b|{
+        b.iter(||{
+            for p in registry.iter_single::<P>() {
+                for p in p {
+                    black_box(p);
+                }
+            }
+        });
+    });
     let p_components = (0..COUNT).into_iter().map(|_|{
         P {
             x: rand::random(),
@@ -18,7 +18,7 @@ struct Rotation {
     w: f32,
 }
 impl Component for Rotation {
-    const NAME: &'static str = "Position";
+    const NAME: &'static str = "Rotation";
 }
 
 #[allow(unused)]
@@ -44,7 +44,8 @@ fn main() {
 
     // We can add a component to the entity:
     let result = registry.add_component(entity, rotation);
-    // In case of failure, for example the entity already has a component of the specific type, an Err(component) is returned.
+    // In case of failure, for example the entity already has a component of the specific type,
+    // an Err(AddComponentError) is returned, which contains the component.
 
     // The can also be removed. This will remove the component returning it if successful.
     // If the entity doesn't have the specified component, it returns an Err().
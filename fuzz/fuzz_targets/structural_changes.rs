@@ -0,0 +1,11 @@
+//! Applies random sequences of entity creation, destruction and component addition and removal.
+//! Run with `cargo fuzz run structural_changes`, which enables AddressSanitizer by default.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shard_ecs::fuzzing::{run, Op};
+
+fuzz_target!(|data: &[u8]| {
+    run(Op::decode(data));
+});
@@ -0,0 +1,11 @@
+use core::fmt;
+
+/// Error returned by fallible reservations if memory could not be allocated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "memory allocation failed")
+    }
+}
@@ -0,0 +1,112 @@
+use crate::ComponentDescriptor;
+use alloc::alloc::{alloc, dealloc, realloc, Layout, LayoutError};
+use core::fmt::Debug;
+use core::ptr::without_provenance_mut;
+
+/// Provides the memory backing the component columns of an archetype.
+/// Archetypes use [`HeapBackend`] unless configured otherwise.
+/// Columns of zero sized components are never allocated through a backend.
+/// # Safety
+/// - Implementations must uphold the same contract as [`core::alloc::GlobalAlloc`].
+/// - Memory returned must stay valid until it is deallocated or reallocated.
+pub unsafe trait ColumnBackend: Send + Sync + Debug {
+    /// Allocates memory for a column as described by `layout`.
+    /// Returns a null pointer if the allocation fails.
+    /// # Safety
+    /// - `layout` must have a non-zero size.
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8;
+
+    /// Resizes the column at `ptr` to `new_size` bytes, keeping its contents.
+    /// Returns a null pointer if the allocation fails, in which case `ptr` stays valid.
+    /// # Safety
+    /// - `ptr` must have been allocated by this backend using `layout`.
+    /// - `new_size` must be non-zero.
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8;
+
+    /// Deallocates the column at `ptr`.
+    /// # Safety
+    /// - `ptr` must have been allocated by this backend using `layout`.
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout);
+}
+
+/// Column backend using the global allocator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeapBackend;
+
+unsafe impl ColumnBackend for HeapBackend {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        alloc(layout)
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        dealloc(ptr, layout)
+    }
+}
+
+/// Returns the layout of a column storing `capacity` components of type `component`.
+/// Columns are allocated, resized and deallocated using this layout, so these always match.
+/// # Safety
+/// - The size of the column must not overflow [`isize`], which holds for every allocated column.
+pub(super) unsafe fn column_layout(component: &ComponentDescriptor, capacity: usize) -> Layout {
+    Layout::from_size_align_unchecked(
+        component.size as usize * capacity,
+        component.column_align as usize,
+    )
+}
+
+/// Returns the layout of a column like [`column_layout`], or Err if its size overflows [`isize`].
+pub(super) fn try_column_layout(
+    component: &ComponentDescriptor,
+    capacity: usize,
+) -> Result<Layout, LayoutError> {
+    Layout::from_size_align(
+        component.size as usize * capacity,
+        component.column_align as usize,
+    )
+}
+
+/// Allocates a column using `backend`.
+/// Zero sized columns are not allocated, these get a dangling pointer aligned to `layout.align()`.
+/// # Safety
+/// - Same as [`ColumnBackend::allocate`], except that `layout` may have a zero size.
+pub(super) unsafe fn allocate_column(backend: &dyn ColumnBackend, layout: Layout) -> *mut u8 {
+    if layout.size() == 0 {
+        return without_provenance_mut(layout.align());
+    }
+    backend.allocate(layout)
+}
+
+/// Resizes a column allocated by [`allocate_column`].
+/// # Safety
+/// - Same as [`ColumnBackend::reallocate`], except that `layout` and `new_size` may be zero.
+pub(super) unsafe fn reallocate_column(
+    backend: &dyn ColumnBackend,
+    ptr: *mut u8,
+    layout: Layout,
+    new_size: usize,
+) -> *mut u8 {
+    if layout.size() == 0 {
+        return allocate_column(
+            backend,
+            Layout::from_size_align_unchecked(new_size, layout.align()),
+        );
+    }
+    if new_size == 0 {
+        backend.deallocate(ptr, layout);
+        return without_provenance_mut(layout.align());
+    }
+    backend.reallocate(ptr, layout, new_size)
+}
+
+/// Deallocates a column allocated by [`allocate_column`].
+/// # Safety
+/// - Same as [`ColumnBackend::deallocate`], except that `layout` may have a zero size.
+pub(super) unsafe fn deallocate_column(backend: &dyn ColumnBackend, ptr: *mut u8, layout: Layout) {
+    if layout.size() != 0 {
+        backend.deallocate(ptr, layout);
+    }
+}
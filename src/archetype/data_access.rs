@@ -1,8 +1,11 @@
-use super::Archetype;
+use super::{
+    allocate_column, column_layout, deallocate_column, reallocate_column, try_column_layout,
+};
+use super::{AllocError, Archetype, RawColumn};
 use crate::*;
 
-use crate::constants::DEFAULT_ARCHETYPE_ALLOCATION_SIZE;
-use alloc::alloc::{dealloc, realloc, Layout};
+use alloc::alloc::{alloc, dealloc, realloc, Layout};
+use alloc::vec::Vec;
 use core::mem::{align_of, size_of};
 use core::ptr::{slice_from_raw_parts, slice_from_raw_parts_mut};
 
@@ -125,6 +128,21 @@ impl Archetype {
         G::slice_unchecked(&pointers, self.len() as usize)
     }
 
+    /// Returns a tuple of component slices covering `len` rows starting at [`first`].
+    /// # Safety
+    /// - Only call this with subsets of the types stored in the archetype.
+    /// - `first + len` must not exceed the amount of entities in the archetype.
+    pub unsafe fn get_fuzzy_slices_range_unchecked<'s, G: ComponentGroup>(
+        &'s self,
+        first: u32,
+        len: u32,
+    ) -> G::SliceRefTuple<'s> {
+        debug_assert!(G::DESCRIPTOR.is_valid());
+        debug_assert!(first as u64 + len as u64 <= self.len() as u64);
+        let pointers = self.get_fuzzy_pointers_unchecked::<G>(first);
+        G::slice_unchecked(&pointers, len as usize)
+    }
+
     /// Returns the mutable slices for the components in [`G`], provided that archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
     /// # Safety:
@@ -138,6 +156,75 @@ impl Archetype {
         G::slice_unchecked_mut(&pointers, self.len() as usize)
     }
 
+    /// Returns the slices for the components in [`G`] if the archetype contains a superset of G.
+    /// Returns None if [`G`] is invalid or not a subset of the types stored in the archetype.
+    pub fn slices_subset<G: ComponentGroup>(&self) -> Option<G::SliceRefTuple<'_>> {
+        if !G::DESCRIPTOR.is_valid() || !self.descriptor.contains_subset(G::DESCRIPTOR.archetype())
+        {
+            return None;
+        }
+        unsafe { Some(self.get_fuzzy_slices_unchecked::<G>()) }
+    }
+
+    /// Returns the mutable slices for the components in [`G`] if the archetype contains a superset of G.
+    /// Returns None if [`G`] is invalid or not a subset of the types stored in the archetype.
+    pub fn slices_subset_mut<G: ComponentGroup>(&mut self) -> Option<G::SliceMutRefTuple<'_>> {
+        if !G::DESCRIPTOR.is_valid() || !self.descriptor.contains_subset(G::DESCRIPTOR.archetype())
+        {
+            return None;
+        }
+        unsafe { Some(self.get_fuzzy_slices_unchecked_mut::<G>()) }
+    }
+
+    /// Returns the column of component [`C`], or None if the archetype does not contain [`C`].
+    pub fn column<C: Component>(&self) -> Option<&[C]> {
+        let idx = self
+            .descriptor
+            .components()
+            .binary_search_by_key(&C::ID, |e| e.component_type_id)
+            .ok()?;
+        unsafe {
+            Some(&*slice_from_raw_parts(
+                self.pointers[idx] as *const C,
+                self.len() as usize,
+            ))
+        }
+    }
+
+    /// Returns the mutable column of component [`C`], or None if the archetype does not contain [`C`].
+    pub fn column_mut<C: Component>(&mut self) -> Option<&mut [C]> {
+        let idx = self
+            .descriptor
+            .components()
+            .binary_search_by_key(&C::ID, |e| e.component_type_id)
+            .ok()?;
+        unsafe {
+            Some(&mut *slice_from_raw_parts_mut(
+                self.pointers[idx] as *mut C,
+                self.len() as usize,
+            ))
+        }
+    }
+
+    /// Returns mutable references to component [`C`] of two different entities in the archetype.
+    /// Returns None if `a == b`, either index is out of bounds or [`C`] is not stored in the archetype.
+    pub fn get_two_components_mut<C: Component>(
+        &mut self,
+        a: u32,
+        b: u32,
+    ) -> Option<(&mut C, &mut C)> {
+        if a == b || a >= self.len() || b >= self.len() {
+            return None;
+        }
+        let idx = self
+            .descriptor
+            .components()
+            .binary_search_by_key(&C::ID, |e| e.component_type_id)
+            .ok()?;
+        let column = self.pointers[idx] as *mut C;
+        unsafe { Some((&mut *column.add(a as usize), &mut *column.add(b as usize))) }
+    }
+
     /// Returns the entity slice and the  slices for the components in [`G`], provided that archetype
     /// itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
@@ -189,11 +276,56 @@ impl Archetype {
         self.entity_count == self.capacity()
     }
 
+    /// Returns true if no more entities can be pushed, as the capacity is limited to
+    /// one less than [`MAX_ENTITIES_PER_ARCHETYPE`].
+    pub fn is_at_entity_limit(&self) -> bool {
+        self.entity_count >= MAX_ENTITIES_PER_ARCHETYPE - 1
+    }
+
+    /// Reserves capacity for at least `additional` more entities.
+    /// Does nothing if the capacity is already sufficient.
+    /// Panics in case of allocation failures.
+    pub fn reserve(&mut self, additional: u32) {
+        // Capacity must stay below the maximum, resize_capacity panics otherwise.
+        let required = (self.len() as u64 + additional as u64)
+            .min(MAX_ENTITIES_PER_ARCHETYPE as u64 - 1) as u32;
+        if required <= self.capacity() {
+            return;
+        }
+        unsafe { self.resize_capacity((required - self.capacity()) as isize) };
+    }
+
+    /// Reserves capacity for at least `additional` more entities, like [`Archetype::reserve`].
+    /// Returns Err if memory could not be allocated, in which case the archetype is left unchanged.
+    pub fn try_reserve(&mut self, additional: u32) -> Result<(), AllocError> {
+        let required = (self.len() as u64 + additional as u64)
+            .min(MAX_ENTITIES_PER_ARCHETYPE as u64 - 1) as u32;
+        if required <= self.capacity() {
+            return Ok(());
+        }
+        unsafe { self.try_grow_to(required) }
+    }
+
     /// Returns a reference to the internal slice storing entity associations.
     pub fn entities(&self) -> &[Entity] {
         unsafe { &*slice_from_raw_parts(self.entity_associations, self.len() as usize) }
     }
 
+    /// Returns an iterator over the type erased component columns of the archetype.
+    /// See [`RawColumn`] for the requirements on dereferencing the column pointers.
+    pub fn raw_columns(&self) -> impl Iterator<Item = RawColumn> + '_ {
+        self.descriptor
+            .components()
+            .iter()
+            .zip(self.pointers.iter())
+            .map(|(component, pointer)| RawColumn {
+                type_id: component.component_type_id(),
+                ptr: *pointer as *const u8,
+                len: self.len(),
+                stride: component.size(),
+            })
+    }
+
     /// Returns a mutable reference to the internal slice storing entity associations.
     pub fn entities_mut(&mut self) -> &mut [Entity] {
         unsafe { &mut *slice_from_raw_parts_mut(self.entity_associations, self.len() as usize) }
@@ -223,6 +355,26 @@ impl Archetype {
         entity_index
     }
 
+    /// Pushes an entity whose components are written by [`write`].
+    /// [`write`] is given a pointer into each column of the new row, sorted like the components of the archetype.
+    /// The entity is only added after [`write`] returns, so a panic leaves the archetype unchanged.
+    /// # Safety
+    /// - [`write`] must write a valid instance of the corresponding component to every pointer.
+    /// - Increases the size of the archetype's memory allocations if required.
+    pub unsafe fn push_entity_with_unchecked<F: FnOnce(&[*mut u8])>(
+        &mut self,
+        entity_handle: Entity,
+        write: F,
+    ) -> u32 {
+        self.resize_if_necessary();
+        let entity_index = self.len();
+        let pointers = self.offset_sorted_pointers_unchecked(entity_index);
+        write(&pointers[0..self.descriptor.len() as usize]);
+        self.entity_count += 1;
+        self.entities_mut()[entity_index as usize] = entity_handle;
+        entity_index
+    }
+
     /// Identical to push_entity_unchecked but does not actually write the entity's component data.
     /// The memory at the the returned index MUST be written with valid component data.
     /// The metadata is not set either.
@@ -233,6 +385,52 @@ impl Archetype {
         entity_index
     }
 
+    /// Writes the components returned by [`f`] into the `count` rows starting at [`first`] in parallel.
+    /// [`f`] is called with the offset of each row relative to [`first`].
+    /// Does not write the entity handles and does not change the amount of entities in the archetype.
+    /// # Safety
+    /// - [`G`] must exactly match the type stored in the archetype.
+    /// - `first + count` must not exceed the capacity of the archetype.
+    /// - The rows must not contain entities, as they are overwritten without calling drop.
+    #[cfg(feature = "parallel")]
+    pub unsafe fn par_write_components_unchecked<G, F>(&mut self, first: u32, count: u32, f: F)
+    where
+        G: ComponentGroup + Send,
+        F: Fn(usize) -> G + Sync,
+    {
+        use rayon::prelude::*;
+
+        struct Columns([*mut u8; MAX_COMPONENTS_PER_ENTITY]);
+        unsafe impl Sync for Columns {}
+        impl Columns {
+            fn get(&self) -> &[*mut u8; MAX_COMPONENTS_PER_ENTITY] {
+                &self.0
+            }
+        }
+
+        debug_assert!(first as u64 + count as u64 <= self.capacity() as u64);
+        debug_assert_eq!(
+            G::DESCRIPTOR.archetype().archetype_id(),
+            self.descriptor.archetype_id()
+        );
+        let columns = Columns(self.pointers);
+        let components = G::DESCRIPTOR.archetype().components();
+        (0..count as usize).into_par_iter().for_each(|offset| {
+            let index = first as usize + offset;
+            let mut entity = f(offset);
+            let mut pointers = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+            entity.as_sorted_pointers(&mut pointers);
+            for (i, component) in components.iter().enumerate() {
+                core::ptr::copy_nonoverlapping::<u8>(
+                    pointers[i],
+                    columns.get()[i].add(component.size as usize * index),
+                    component.size as usize,
+                );
+            }
+            core::mem::forget(entity);
+        });
+    }
+
     /// Decrements archetype size by 1, therefore assuming the last entity is moved elsewhere.
     /// As such, it does not call drop on the last entity.
     pub unsafe fn decrement_len_unchecked(&mut self) {
@@ -260,6 +458,36 @@ impl Archetype {
         }
     }
 
+    /// Overwrites the component with the given type id at a specific position with the provided bytes.
+    /// The existing component at index is dropped first.
+    /// Panics if called on an archetype that does not contain the component.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - [`bytes`] must be a valid instance of the component, with exactly the size of the component.
+    /// - The archetype takes ownership of the instance, it must not be dropped elsewhere.
+    pub unsafe fn write_component_bytes_unchecked(
+        &mut self,
+        index: u32,
+        component_type_id: ComponentTypeId,
+        bytes: &[u8],
+    ) {
+        debug_assert!(index < self.len());
+        match self
+            .descriptor
+            .components()
+            .binary_search_by_key(&component_type_id, |e| e.component_type_id)
+        {
+            Ok(idx) => {
+                let descriptor = &self.descriptor.components()[idx];
+                debug_assert_eq!(bytes.len(), descriptor.size as usize);
+                let pointer = self.pointers[idx].add(descriptor.size as usize * index as usize);
+                (descriptor.fns.drop_handler)(pointer, 1);
+                core::ptr::copy_nonoverlapping(bytes.as_ptr(), pointer, bytes.len());
+            }
+            Err(_) => unreachable!(),
+        }
+    }
+
     /// Writes a given entity/component-tuple into the archetype's backing memory.
     /// # Safety:
     /// - Must be called exactly with the component group contained in the archetype.
@@ -345,32 +573,39 @@ impl Archetype {
     /// This effectively reduces the size of the archetype by 1, returning the entity at index.
     /// And moving the previously last entity to the position at index.
     /// If [`index`] is the last element, simply returns it instead without any swaps occurring.
-    /// Returns true if a swap occurred, or false if not.
+    /// Returns the handle of the entity moved to [`index`] if a swap occurred, or None if not.
     /// # Safety:
     /// - [`index`] must be smaller than the amount of entities in the archetype.
     /// - [`G`] must exactly match the type store in the archetype.
     /// - Ordering of component in [`G`] may be different.
-    pub unsafe fn swap_remove_unchecked<G: ComponentGroup>(&mut self, index: u32) -> (G, bool) {
+    pub unsafe fn swap_remove_unchecked<G: ComponentGroup>(
+        &mut self,
+        index: u32,
+    ) -> (G, Option<Entity>) {
         debug_assert!(index < self.len());
         if index == self.len() - 1 {
             // Is the last one, so just drop it.
             let data: G = self.read_components_exact_unchecked::<G>(index);
             self.entity_count -= 1;
-            (data, false)
+            (data, None)
         } else {
             self.swap_entities(index, self.len() - 1);
             let data: G = self.read_components_exact_unchecked(self.len() - 1);
             self.entity_count -= 1;
-            (data, true)
+            (data, Some(*self.entity_associations.add(index as usize)))
         }
     }
 
     /// Swaps the entities at the provided positions.
+    /// Swapping an entity with itself does nothing.
     /// # Safety:
     /// - [`first`] must be smaller than the amount of entities in the archetype.
     /// - [`second`] must be smaller than the amount of entities in the archetype.
-    /// - [`first`] must not be equal to [`last`].
     pub unsafe fn swap_entities(&mut self, first: u32, second: u32) {
+        // The columns would overlap, which swap_nonoverlapping does not allow.
+        if first == second {
+            return;
+        }
         for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
             let ptr_first = self.pointers[idx].offset(first as isize * descriptor.size as isize);
             let ptr_second = self.pointers[idx].offset(second as isize * descriptor.size as isize);
@@ -416,15 +651,43 @@ impl Archetype {
     /// - Does not call drop on the entities in the backing storage.
     /// - Panics if resizing fails for whatever reason. This leaves the archetype in an undefined state.
     /// - Deallocates if the new capacity is smaller or equal to zero.
-    /// - Deallocates if the new capacity exceeds [`MAX_ENTITIES_PER_ARCHETYPE`]. TODO: This is weird?
+    /// - Clamps the new capacity to one less than [`MAX_ENTITIES_PER_ARCHETYPE`],
+    ///   panics if the archetype cannot grow any further.
     pub(super) unsafe fn resize_capacity(&mut self, change_in_entity_count: isize) {
         let old_capacity = self.capacity();
         let new_capacity = old_capacity as isize + change_in_entity_count;
-        if new_capacity <= 0 || new_capacity >= MAX_ENTITIES_PER_ARCHETYPE as isize {
+        if new_capacity <= 0 {
             self.dealloc();
             return;
         }
-        let new_capacity = new_capacity as usize;
+        // Row indices must fit in an entity entry, which excludes the maximum itself.
+        let new_capacity = new_capacity.min(MAX_ENTITIES_PER_ARCHETYPE as isize - 1) as usize;
+        assert!(
+            new_capacity > old_capacity as usize,
+            "An archetype cannot store more than {} entities.",
+            MAX_ENTITIES_PER_ARCHETYPE - 1
+        );
+
+        // Nothing is allocated without capacity, so there is nothing to reallocate.
+        if old_capacity == 0 {
+            let layout = Layout::from_size_align_unchecked(
+                size_of::<Entity>() * new_capacity,
+                align_of::<Entity>(),
+            );
+            self.entity_associations = alloc(layout) as *mut Entity;
+            assert_ne!(self.entity_associations, core::ptr::null_mut());
+            for (index, pointer) in self.pointers[0..self.descriptor.len() as usize]
+                .iter_mut()
+                .enumerate()
+            {
+                let component_type = &self.descriptor.components()[index];
+                let layout = column_layout(component_type, new_capacity);
+                *pointer = allocate_column(self.backend, layout);
+                assert_ne!(*pointer, core::ptr::null_mut());
+            }
+            self.capacity = new_capacity as u32;
+            return;
+        }
 
         let layout = Layout::from_size_align_unchecked(
             size_of::<Entity>() * old_capacity as usize,
@@ -441,11 +704,9 @@ impl Archetype {
             .enumerate()
         {
             let component_type = &self.descriptor.components()[index];
-            let layout = Layout::from_size_align_unchecked(
-                component_type.size as usize * old_capacity as usize,
-                component_type.align as usize,
-            );
-            *pointer = realloc(
+            let layout = column_layout(component_type, old_capacity as usize);
+            *pointer = reallocate_column(
+                self.backend,
                 *pointer,
                 layout,
                 component_type.size as usize * new_capacity,
@@ -455,10 +716,61 @@ impl Archetype {
         self.capacity = new_capacity as u32;
     }
 
+    /// Moves the backing memory to newly allocated memory for `new_capacity` entities.
+    /// The old memory is only released once all new memory is allocated,
+    /// so the archetype is left unchanged if an allocation fails.
+    /// # Safety
+    /// - `new_capacity` must be larger than the current capacity.
+    unsafe fn try_grow_to(&mut self, new_capacity: u32) -> Result<(), AllocError> {
+        let new_capacity = new_capacity as usize;
+        let entity_layout = Layout::array::<Entity>(new_capacity).map_err(|_| AllocError)?;
+        let entity_associations = alloc(entity_layout) as *mut Entity;
+        if entity_associations.is_null() {
+            return Err(AllocError);
+        }
+        let components = self.descriptor.components();
+        let mut pointers = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+        for (index, component) in components.iter().enumerate() {
+            pointers[index] = match try_column_layout(component, new_capacity) {
+                Ok(layout) => allocate_column(self.backend, layout),
+                Err(_) => core::ptr::null_mut(),
+            };
+            if pointers[index].is_null() {
+                for (pointer, component) in pointers[0..index].iter().zip(components) {
+                    let layout = try_column_layout(component, new_capacity).unwrap();
+                    deallocate_column(self.backend, *pointer, layout);
+                }
+                dealloc(entity_associations as *mut u8, entity_layout);
+                return Err(AllocError);
+            }
+        }
+
+        let len = self.len() as usize;
+        if self.capacity > 0 {
+            core::ptr::copy_nonoverlapping(self.entity_associations, entity_associations, len);
+            for (index, component) in components.iter().enumerate() {
+                core::ptr::copy_nonoverlapping(
+                    self.pointers[index],
+                    pointers[index],
+                    component.size as usize * len,
+                );
+            }
+            self.dealloc();
+        }
+        self.entity_associations = entity_associations;
+        self.pointers = pointers;
+        self.capacity = new_capacity as u32;
+        Ok(())
+    }
+
     /// Deallocates the backing memory and sets capacity to zero.
     /// # Safety:
     /// - Does not call drop on the entities in the backing storage.
     pub(super) unsafe fn dealloc(&mut self) {
+        // Nothing is allocated without capacity, which also makes deallocating twice a no-op.
+        if self.capacity == 0 {
+            return;
+        }
         for (index, pointer) in self.pointers[0..self.descriptor.len() as usize]
             .iter_mut()
             .enumerate()
@@ -467,11 +779,8 @@ impl Archetype {
                 return;
             }
             let component_type = &self.descriptor.components()[index];
-            let layout = Layout::from_size_align_unchecked(
-                component_type.size as usize * self.capacity as usize,
-                component_type.align as usize,
-            );
-            dealloc(*pointer, layout);
+            let layout = column_layout(component_type, self.capacity as usize);
+            deallocate_column(self.backend, *pointer, layout);
             *pointer = core::ptr::null_mut();
         }
         let layout = Layout::from_size_align_unchecked(
@@ -499,13 +808,14 @@ impl Archetype {
         pointers
     }
 
-    /// Resizes the backing memory by the default amount if necessary.
+    /// Resizes the backing memory if necessary.
+    /// Allocates the initial capacity if the archetype has no capacity at all.
     unsafe fn resize_if_necessary(&mut self) {
         if self.is_full() {
             let additional_capacity = if self.capacity() == 0 {
-                DEFAULT_ARCHETYPE_ALLOCATION_SIZE
+                self.initial_capacity
             } else {
-                self.capacity() as usize
+                self.capacity()
             };
             self.resize_capacity(additional_capacity as isize);
         }
@@ -536,6 +846,205 @@ impl Archetype {
         }
     }
 
+    /// Moves the entity at [`index`] into a new row at the end of [`destination`].
+    /// Components present in both archetypes are copied, the previously last entity is moved to [`index`].
+    /// Returns the index of the new row and the handle of the entity moved to [`index`], if any.
+    /// # Safety
+    /// - [`index`] must be smaller than the amount of entities in the archetype.
+    /// - Components missing in [`destination`] are not dropped, they must be read or dropped beforehand.
+    /// - Components missing in this archetype must be written to the new row afterwards.
+    pub unsafe fn move_row_to(
+        &mut self,
+        index: u32,
+        destination: &mut Archetype,
+    ) -> (u32, Option<Entity>) {
+        debug_assert!(index < self.len());
+        let destination_index = destination.push_uninitialized_entity();
+        Archetype::copy_common_components_between_archetypes_unchecked(
+            self,
+            index,
+            destination,
+            destination_index,
+        );
+        destination.entities_mut()[destination_index as usize] = self.entities()[index as usize];
+
+        let last_index = self.len() - 1;
+        let moved_entity = if index == last_index {
+            None
+        } else {
+            for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+                core::ptr::copy_nonoverlapping(
+                    self.pointers[idx].offset(last_index as isize * descriptor.size as isize),
+                    self.pointers[idx].offset(index as isize * descriptor.size as isize),
+                    descriptor.size as usize,
+                );
+            }
+            let moved_entity = self.entities()[last_index as usize];
+            self.entities_mut()[index as usize] = moved_entity;
+            Some(moved_entity)
+        };
+        self.entity_count -= 1;
+        (destination_index, moved_entity)
+    }
+
+    /// Drops all entities in the archetype, keeping its memory allocated.
+    pub fn clear(&mut self) {
+        let len = self.len() as usize;
+        // Emptied first, so a panicking drop only leaks.
+        self.entity_count = 0;
+        for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+            unsafe { (descriptor.fns.drop_handler)(self.pointers[idx], len) };
+        }
+    }
+
+    /// Appends clones of all entities of [`source`] to the archetype, including their entity handles.
+    /// If a clone handler panics, the archetype is left unchanged and already cloned components are leaked.
+    /// # Safety
+    /// - [`source`] must have the same components as this archetype.
+    /// - Every component of the archetype must have a clone handler.
+    pub unsafe fn clone_rows_from(&mut self, source: &Archetype) {
+        debug_assert_eq!(
+            self.descriptor.archetype_id(),
+            source.descriptor.archetype_id()
+        );
+        let count = source.len();
+        if count == 0 {
+            return;
+        }
+        self.reserve(count);
+        let first = self.len() as usize;
+        for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+            let clone_handler = descriptor.fns.clone_handler.unwrap();
+            clone_handler(
+                source.pointers[idx],
+                self.pointers[idx].add(descriptor.size as usize * first),
+                count as usize,
+            );
+        }
+        core::ptr::copy_nonoverlapping(
+            source.entity_associations,
+            self.entity_associations.add(first),
+            count as usize,
+        );
+        self.entity_count += count;
+    }
+
+    /// Removes the rows for which [`is_live`] returns false, by moving the remaining rows into the gaps.
+    /// The remaining rows keep their relative order.
+    /// Returns the amount of removed rows.
+    /// # Safety
+    /// - Removed rows are considered to not contain valid components, they are not dropped.
+    pub unsafe fn compact(&mut self, is_live: impl Fn(u32) -> bool) -> u32 {
+        let len = self.len();
+        let mut live_count = 0;
+        for index in 0..len {
+            if !is_live(index) {
+                continue;
+            }
+            if index != live_count {
+                for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+                    core::ptr::copy_nonoverlapping(
+                        self.pointers[idx].add(index as usize * descriptor.size as usize),
+                        self.pointers[idx].add(live_count as usize * descriptor.size as usize),
+                        descriptor.size as usize,
+                    );
+                }
+                *self.entity_associations.add(live_count as usize) =
+                    *self.entity_associations.add(index as usize);
+            }
+            live_count += 1;
+        }
+        self.entity_count = live_count;
+        len - live_count
+    }
+
+    /// Moves all entities into new rows at the end of [`destination`], leaving this archetype empty.
+    /// Components present in both archetypes are copied one column at a time,
+    /// components missing in [`destination`] are dropped.
+    /// Returns the index of the first new row and the amount of moved entities.
+    /// # Safety
+    /// - The entities of both archetypes combined must fit in [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// - Components missing in this archetype must be written to the new rows afterwards.
+    pub unsafe fn move_all_rows_to(&mut self, destination: &mut Archetype) -> (u32, u32) {
+        self.move_all_rows_to_skipping_drop(destination, None)
+    }
+
+    /// Moves all entities into new rows at the end of [`destination`], like [`Archetype::move_all_rows_to`],
+    /// appending the values of component [`C`] to [`extracted`] in row order instead of dropping them.
+    /// Returns the index of the first new row and the amount of moved entities.
+    /// # Safety
+    /// - The entities of both archetypes combined must fit in [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// - [`C`] must be present in this archetype and missing in [`destination`].
+    /// - Components missing in this archetype must be written to the new rows afterwards.
+    pub unsafe fn move_all_rows_to_extracting<C: Component>(
+        &mut self,
+        destination: &mut Archetype,
+        extracted: &mut Vec<C>,
+    ) -> (u32, u32) {
+        debug_assert!(self.descriptor.has_component::<C>());
+        debug_assert!(!destination.descriptor.has_component::<C>());
+        // Allocate up front, so nothing can panic while the values exist twice.
+        destination.reserve(self.len());
+        extracted.reserve(self.len() as usize);
+        let column = self.column::<C>().unwrap();
+        let first = extracted.len();
+        core::ptr::copy_nonoverlapping(
+            column.as_ptr(),
+            extracted.as_mut_ptr().add(first),
+            column.len(),
+        );
+        extracted.set_len(first + column.len());
+        self.move_all_rows_to_skipping_drop(destination, Some(C::ID))
+    }
+
+    unsafe fn move_all_rows_to_skipping_drop(
+        &mut self,
+        destination: &mut Archetype,
+        skip_drop: Option<ComponentTypeId>,
+    ) -> (u32, u32) {
+        let count = self.len();
+        let first = destination.len();
+        if count == 0 {
+            return (first, 0);
+        }
+        destination.reserve(count);
+        core::ptr::copy_nonoverlapping(
+            self.entity_associations,
+            destination.entity_associations.add(first as usize),
+            count as usize,
+        );
+        for (source_c_idx, source_component) in self.descriptor.components().iter().enumerate() {
+            let destination_c_idx = destination
+                .descriptor
+                .components()
+                .binary_search_by_key(&source_component.component_type_id, |e| e.component_type_id);
+            match destination_c_idx {
+                Ok(destination_c_idx) => core::ptr::copy_nonoverlapping(
+                    self.pointers[source_c_idx],
+                    destination.pointers[destination_c_idx]
+                        .add(source_component.size as usize * first as usize),
+                    source_component.size as usize * count as usize,
+                ),
+                Err(_) => continue,
+            }
+        }
+        self.entity_count = 0;
+        destination.entity_count += count;
+        // Both archetypes are consistent at this point, so a panicking drop only leaks.
+        for (idx, descriptor) in self.descriptor.components().iter().enumerate() {
+            if destination
+                .descriptor
+                .components()
+                .binary_search_by_key(&descriptor.component_type_id, |e| e.component_type_id)
+                .is_err()
+                && skip_drop != Some(descriptor.component_type_id)
+            {
+                (descriptor.fns.drop_handler)(self.pointers[idx], count as usize);
+            }
+        }
+        (first, count)
+    }
+
     /// Returns the pointers for the components in [`G`], provided that archetype itself contains a superset of G.
     /// This function is slower than the exact version, use that if an exact type match is known.
     /// # Safety:
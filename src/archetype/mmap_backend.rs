@@ -0,0 +1,82 @@
+use super::ColumnBackend;
+use alloc::alloc::Layout;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+/// Column backend storing every column in a memory mapped file, which the operating system pages in and out.
+/// This allows archetypes to store more component data than fits in memory.
+/// Files are created in [`MmapBackend::directory`] and unlinked right away, so none are left behind.
+/// Columns aligned to more than the page size cannot be allocated.
+#[derive(Debug, Clone, Copy)]
+pub struct MmapBackend {
+    directory: &'static CStr,
+}
+
+impl MmapBackend {
+    /// Creates a backend which creates its files in `directory`.
+    pub const fn new(directory: &'static CStr) -> Self {
+        Self { directory }
+    }
+
+    /// Returns the directory in which the files backing the columns are created.
+    pub const fn directory(&self) -> &'static CStr {
+        self.directory
+    }
+
+    /// Creates an unlinked file of `size` bytes, returns its file descriptor or -1 on failure.
+    unsafe fn create_file(&self, size: usize) -> libc::c_int {
+        let mut path = Vec::from(self.directory.to_bytes());
+        path.extend_from_slice(b"/shard-column-XXXXXX\0");
+        let fd = libc::mkstemp(path.as_mut_ptr() as *mut libc::c_char);
+        if fd == -1 {
+            return -1;
+        }
+        libc::unlink(path.as_ptr() as *const libc::c_char);
+        if libc::ftruncate(fd, size as libc::off_t) != 0 {
+            libc::close(fd);
+            return -1;
+        }
+        fd
+    }
+}
+
+unsafe impl ColumnBackend for MmapBackend {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        // Mappings are aligned to the page size.
+        if layout.align() > libc::sysconf(libc::_SC_PAGESIZE) as usize {
+            return core::ptr::null_mut();
+        }
+        let fd = self.create_file(layout.size());
+        if fd == -1 {
+            return core::ptr::null_mut();
+        }
+        let ptr = libc::mmap(
+            core::ptr::null_mut(),
+            layout.size(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        );
+        // The mapping keeps the file alive.
+        libc::close(fd);
+        if ptr == libc::MAP_FAILED {
+            return core::ptr::null_mut();
+        }
+        ptr as *mut u8
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.allocate(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if new_ptr.is_null() {
+            return new_ptr;
+        }
+        core::ptr::copy_nonoverlapping(ptr, new_ptr, core::cmp::min(layout.size(), new_size));
+        self.deallocate(ptr, layout);
+        new_ptr
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        libc::munmap(ptr as *mut libc::c_void, layout.size());
+    }
+}
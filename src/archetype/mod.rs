@@ -4,10 +4,28 @@ use crate::*;
 use alloc::alloc::{alloc, Layout};
 use core::mem::{align_of, size_of};
 
+mod alloc_error;
+mod column_backend;
 mod data_access;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap_backend;
+mod raw_column;
+#[cfg(feature = "pool")]
+mod storage_pool;
 #[cfg(test)]
 mod tests;
 
+pub use alloc_error::AllocError;
+use column_backend::{
+    allocate_column, column_layout, deallocate_column, reallocate_column, try_column_layout,
+};
+pub use column_backend::{ColumnBackend, HeapBackend};
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap_backend::MmapBackend;
+pub use raw_column::RawColumn;
+#[cfg(feature = "pool")]
+pub use storage_pool::StoragePool;
+
 #[derive(Debug)]
 pub struct Archetype {
     descriptor: ArchetypeDescriptor,
@@ -15,6 +33,11 @@ pub struct Archetype {
     entity_associations: *mut Entity,
     entity_count: u32,
     capacity: u32,
+    initial_capacity: u32,
+    last_structural_change: Tick,
+    last_write: Tick,
+    backend: &'static dyn ColumnBackend,
+    stable_id: StableArchetypeId,
 }
 
 unsafe impl Send for Archetype {}
@@ -33,21 +56,28 @@ impl Archetype {
     }
 
     /// Allocates an archetype with a given capacity for storing data into it.
+    /// The capacity is remembered and used for allocating if the archetype is empty and has no capacity left.
     /// Panics if the provided archetype descriptor is invalid.
-    /// Does not allocate if [`capacity`] exceeds [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does not allocate if [`capacity`] is not below [`MAX_ENTITIES_PER_ARCHETYPE`].
     /// Does not allocate if [`capacity`] is 0.
     /// Panics in case of allocation failures.
     pub fn with_capacity(archetype_descriptor: &ArchetypeDescriptor, capacity: u32) -> Archetype {
-        assert!(archetype_descriptor.is_valid());
-        let mut archetype = Self {
-            descriptor: archetype_descriptor.clone(),
-            pointers: [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY],
-            entity_associations: core::ptr::null_mut(),
-            entity_count: 0,
-            capacity: 0,
-        };
+        Self::with_capacity_in(archetype_descriptor, capacity, &HeapBackend)
+    }
+
+    /// Allocates an archetype with a given capacity, storing its component columns in the given backend.
+    /// Panics if the provided archetype descriptor is invalid.
+    /// Does not allocate if [`capacity`] is not below [`MAX_ENTITIES_PER_ARCHETYPE`].
+    /// Does not allocate if [`capacity`] is 0.
+    /// Panics in case of allocation failures.
+    pub fn with_capacity_in(
+        archetype_descriptor: &ArchetypeDescriptor,
+        capacity: u32,
+        backend: &'static dyn ColumnBackend,
+    ) -> Archetype {
+        let mut archetype = Self::unallocated_in(archetype_descriptor, capacity, backend);
         // Allocate
-        if capacity > 0 && capacity <= MAX_ENTITIES_PER_ARCHETYPE {
+        if capacity > 0 && capacity < MAX_ENTITIES_PER_ARCHETYPE {
             unsafe {
                 let layout = Layout::from_size_align_unchecked(
                     size_of::<Entity>() * capacity as usize,
@@ -57,11 +87,8 @@ impl Archetype {
                 assert_ne!(archetype.entity_associations, core::ptr::null_mut());
 
                 for (index, component) in archetype.descriptor.components().iter().enumerate() {
-                    let layout = Layout::from_size_align_unchecked(
-                        component.size as usize * capacity as usize,
-                        component.align as usize,
-                    );
-                    archetype.pointers[index] = alloc(layout);
+                    let layout = column_layout(component, capacity as usize);
+                    archetype.pointers[index] = allocate_column(archetype.backend, layout);
                     assert_ne!(archetype.pointers[index], core::ptr::null_mut());
                 }
                 archetype.capacity = capacity;
@@ -70,9 +97,84 @@ impl Archetype {
         archetype
     }
 
+    /// Creates an archetype without allocating, storing its component columns in the given backend.
+    /// [`initial_capacity`] is remembered like in [`Archetype::with_capacity_in`].
+    /// Panics if the provided archetype descriptor is invalid.
+    pub fn unallocated_in(
+        archetype_descriptor: &ArchetypeDescriptor,
+        initial_capacity: u32,
+        backend: &'static dyn ColumnBackend,
+    ) -> Archetype {
+        assert!(archetype_descriptor.is_valid());
+        Self {
+            descriptor: archetype_descriptor.clone(),
+            pointers: [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY],
+            entity_associations: core::ptr::null_mut(),
+            entity_count: 0,
+            capacity: 0,
+            initial_capacity: if initial_capacity > 0
+                && initial_capacity < MAX_ENTITIES_PER_ARCHETYPE
+            {
+                initial_capacity
+            } else {
+                DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32
+            },
+            last_structural_change: Tick::ZERO,
+            last_write: Tick::ZERO,
+            backend,
+            stable_id: StableArchetypeId::INVALID,
+        }
+    }
+
     pub fn descriptor(&self) -> &ArchetypeDescriptor {
         &self.descriptor
     }
+
+    /// Returns the tick at which entities were last added to or removed from the archetype.
+    pub fn last_structural_change(&self) -> Tick {
+        self.last_structural_change
+    }
+
+    /// Returns the tick at which mutable access to components of the archetype was last handed out.
+    pub fn last_write(&self) -> Tick {
+        self.last_write
+    }
+
+    /// Returns true if the archetype changed structurally or was written to after the given tick.
+    pub fn changed_since(&self, tick: Tick) -> bool {
+        self.last_structural_change > tick || self.last_write > tick
+    }
+
+    /// Records a structural change of the archetype at the given tick.
+    pub fn mark_structural_change(&mut self, tick: Tick) {
+        self.last_structural_change = tick;
+    }
+
+    /// Records mutable access to components of the archetype at the given tick.
+    pub fn mark_write(&mut self, tick: Tick) {
+        self.last_write = tick;
+    }
+
+    /// Returns the capacity the archetype was created with.
+    /// Falls back to [`DEFAULT_ARCHETYPE_ALLOCATION_SIZE`] if that capacity could not be allocated.
+    pub fn initial_capacity(&self) -> u32 {
+        self.initial_capacity
+    }
+
+    /// Returns the stable id assigned by the registry owning the archetype.
+    /// Returns [`StableArchetypeId::INVALID`] if the archetype is not part of a registry.
+    pub fn stable_id(&self) -> StableArchetypeId {
+        self.stable_id
+    }
+
+    pub(crate) fn set_stable_id(&mut self, stable_id: StableArchetypeId) {
+        self.stable_id = stable_id;
+    }
+
+    /// Returns the backend storing the component columns of the archetype.
+    pub fn column_backend(&self) -> &'static dyn ColumnBackend {
+        self.backend
+    }
 }
 
 impl Drop for Archetype {
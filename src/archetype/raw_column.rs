@@ -0,0 +1,37 @@
+use crate::ComponentTypeId;
+
+/// Describes the type erased backing memory of a single component column in an archetype.
+/// A column stores [`RawColumn::len`] components contiguously, each [`RawColumn::stride`] bytes in size.
+/// # Safety:
+/// - [`RawColumn::ptr`] is only valid as long as the archetype it was obtained from is not modified.
+/// - Any structural change (adding, removing or moving entities) may invalidate the pointer.
+/// - The pointed to memory must not be written to through this pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawColumn {
+    /// The type of the components stored in the column.
+    pub type_id: ComponentTypeId,
+    /// Pointer to the first component in the column.
+    pub ptr: *const u8,
+    /// The amount of components stored in the column.
+    pub len: u32,
+    /// The size in bytes of a single component in the column.
+    pub stride: u16,
+}
+
+impl RawColumn {
+    /// Returns the amount of bytes spanned by the column.
+    pub const fn size_in_bytes(&self) -> usize {
+        self.len as usize * self.stride as usize
+    }
+
+    /// Returns the column's memory as a byte slice.
+    /// # Safety
+    /// - The archetype the column was obtained from must not be modified while the slice is alive.
+    /// - Components containing padding bytes may expose uninitialized memory.
+    pub unsafe fn as_bytes<'a>(&self) -> &'a [u8] {
+        if self.size_in_bytes() == 0 {
+            return &[];
+        }
+        core::slice::from_raw_parts(self.ptr, self.size_in_bytes())
+    }
+}
@@ -0,0 +1,42 @@
+use super::ColumnBackend;
+use alloc::alloc::Layout;
+use core::fmt::Debug;
+
+/// Hands out whole blocks of memory for component columns and takes them back for reuse.
+/// Every pool is a [`ColumnBackend`], growing a column acquires a new block and releases the old one.
+/// # Safety
+/// - Blocks returned by `acquire` must be valid for reads and writes of `layout.size()` bytes,
+///   aligned to `layout.align()` and not handed out again until they are released.
+pub unsafe trait StoragePool: Send + Sync + Debug {
+    /// Acquires a block of memory for a column as described by `layout`.
+    /// Returns a null pointer if no block is available.
+    /// # Safety
+    /// - `layout` must have a non-zero size.
+    unsafe fn acquire(&self, layout: Layout) -> *mut u8;
+
+    /// Returns the block at `ptr` to the pool.
+    /// # Safety
+    /// - `ptr` must have been acquired from this pool using `layout`.
+    unsafe fn release(&self, ptr: *mut u8, layout: Layout);
+}
+
+unsafe impl<P: StoragePool> ColumnBackend for P {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        self.acquire(layout)
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.acquire(new_layout);
+        if new_ptr.is_null() {
+            return new_ptr;
+        }
+        core::ptr::copy_nonoverlapping(ptr, new_ptr, core::cmp::min(layout.size(), new_size));
+        self.release(ptr, layout);
+        new_ptr
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        self.release(ptr, layout)
+    }
+}
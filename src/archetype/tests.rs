@@ -2,7 +2,7 @@ use crate::archetype::Archetype;
 use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
 use crate::descriptors::component_group::ComponentGroup;
 use crate::test_components::*;
-use crate::{Entity, MAX_ENTITIES_PER_ARCHETYPE};
+use crate::{Component, Entity, DEFAULT_ARCHETYPE_ALLOCATION_SIZE, MAX_ENTITIES_PER_ARCHETYPE};
 
 #[test]
 fn test_archetype_constructors() {
@@ -10,7 +10,11 @@ fn test_archetype_constructors() {
     // Empty constructor should not panic
     let _ = Archetype::new(descriptor);
     Archetype::with_capacity(descriptor, 0);
-    Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE);
+    // Capacity is limited to one less than the maximum.
+    assert_eq!(
+        Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE).capacity(),
+        0
+    );
     Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE + 1);
 }
 
@@ -195,3 +199,444 @@ fn test_archetype_read_components() {
         );
     }
 }
+
+#[test]
+fn test_archetype_slices_subset() {
+    let descriptor = <(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    unsafe {
+        archetype.push_entity_unchecked(
+            Entity::INVALID,
+            (A { _data: 1 }, B { _data: 2 }, C { _data: 3 }),
+        );
+    }
+
+    let (c, a) = archetype.slices_subset_mut::<(C, A)>().unwrap();
+    c[0]._data = 30;
+    a[0]._data = 10;
+    let (a, b, c) = archetype.slices_subset::<(A, B, C)>().unwrap();
+    assert_eq!(a, &[A { _data: 10 }]);
+    assert_eq!(b, &[B { _data: 2 }]);
+    assert_eq!(c, &[C { _data: 30 }]);
+
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    assert!(archetype.slices_subset_mut::<(A, C)>().is_none());
+    assert!(archetype.slices_subset::<C>().is_none());
+}
+
+#[test]
+fn test_archetype_raw_columns() {
+    let descriptor = <(B, A) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    unsafe {
+        archetype.push_entity_unchecked(Entity::INVALID, (A { _data: 1 }, B { _data: 3 }));
+        archetype.push_entity_unchecked(Entity::INVALID, (A { _data: 2 }, B { _data: 4 }));
+    }
+    let columns = archetype.raw_columns().collect::<alloc::vec::Vec<_>>();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].type_id, A::ID);
+    assert_eq!(columns[1].type_id, B::ID);
+    for column in &columns {
+        assert_eq!(column.len, 2);
+        assert_eq!(column.stride as usize, core::mem::size_of::<usize>());
+        assert_eq!(column.size_in_bytes(), 2 * core::mem::size_of::<usize>());
+    }
+    let (a, b) = unsafe { archetype.get_slices_unchecked_exact::<(A, B)>() };
+    assert_eq!(columns[0].ptr, a.as_ptr() as *const u8);
+    assert_eq!(columns[1].ptr, b.as_ptr() as *const u8);
+    let bytes = unsafe { columns[1].as_bytes() };
+    assert_eq!(
+        bytes[0..core::mem::size_of::<usize>()],
+        3usize.to_ne_bytes()
+    );
+}
+
+#[test]
+fn test_archetype_reserve() {
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    unsafe {
+        archetype.push_entity_unchecked(Entity::INVALID, (A { _data: 1 }, B { _data: 2 }));
+    }
+    archetype.reserve(1000);
+    assert!(archetype.capacity() >= 1001);
+    let capacity = archetype.capacity();
+    archetype.reserve(10);
+    assert_eq!(archetype.capacity(), capacity);
+    assert_eq!(
+        unsafe { archetype.read_components_exact_unchecked::<(A, B)>(0) },
+        (A { _data: 1 }, B { _data: 2 })
+    );
+}
+
+#[test]
+fn test_archetype_get_two_components_mut() {
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    unsafe {
+        archetype.push_entity_unchecked(Entity::INVALID, (A { _data: 1 }, B { _data: 2 }));
+        archetype.push_entity_unchecked(Entity::INVALID, (A { _data: 3 }, B { _data: 4 }));
+    }
+    let (first, second) = archetype.get_two_components_mut::<A>(0, 1).unwrap();
+    core::mem::swap(first, second);
+    assert_eq!(
+        unsafe { archetype.read_components_exact_unchecked::<(A, B)>(0) },
+        (A { _data: 3 }, B { _data: 2 })
+    );
+    assert_eq!(
+        unsafe { archetype.read_components_exact_unchecked::<(A, B)>(1) },
+        (A { _data: 1 }, B { _data: 4 })
+    );
+    assert!(archetype.get_two_components_mut::<A>(1, 1).is_none());
+    assert!(archetype.get_two_components_mut::<A>(0, 2).is_none());
+    assert!(archetype.get_two_components_mut::<C>(0, 1).is_none());
+}
+
+#[test]
+fn test_archetype_initial_capacity() {
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let archetype = Archetype::with_capacity(descriptor, 0);
+    assert_eq!(archetype.capacity(), 0);
+    assert_eq!(
+        archetype.initial_capacity(),
+        DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32
+    );
+    let archetype = Archetype::with_capacity(descriptor, 3);
+    assert_eq!(archetype.initial_capacity(), 3);
+    assert_eq!(archetype.capacity(), 3);
+}
+
+#[test]
+fn test_archetype_column_align() {
+    #[derive(Debug, Default)]
+    struct Aligned {
+        _data: u8,
+    }
+    impl Component for Aligned {
+        const NAME: &'static str = "Aligned";
+        const COLUMN_ALIGN: usize = 64;
+    }
+
+    assert_eq!(<Aligned as Component>::DESCRIPTOR.align(), 1);
+    assert_eq!(<Aligned as Component>::DESCRIPTOR.column_align(), 64);
+    assert_eq!(
+        <A as Component>::DESCRIPTOR.column_align() as usize,
+        core::mem::align_of::<A>()
+    );
+
+    let descriptor = <(A, Aligned) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::with_capacity(descriptor, 1);
+    for _ in 0..100 {
+        unsafe {
+            archetype.push_entity_unchecked(Entity::INVALID, (A::default(), Aligned::default()));
+        }
+        let column = archetype
+            .raw_columns()
+            .find(|column| column.type_id == Aligned::ID)
+            .unwrap();
+        assert_eq!(column.ptr as usize % 64, 0);
+    }
+}
+
+#[test]
+fn test_archetype_swap_remove_returns_moved_entity() {
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    let entities = (0..3)
+        .map(|e| unsafe { Entity::new_unchecked(e, 0) })
+        .collect::<alloc::vec::Vec<_>>();
+    for (e, entity) in entities.iter().enumerate() {
+        unsafe {
+            archetype.push_entity_unchecked(*entity, (A { _data: e }, B { _data: e }));
+        }
+    }
+    let (removed, moved) = unsafe { archetype.swap_remove_unchecked::<(B, A)>(0) };
+    assert_eq!(removed, (B { _data: 0 }, A { _data: 0 }));
+    assert_eq!(moved, Some(entities[2]));
+    assert_eq!(archetype.entities(), &[entities[2], entities[1]]);
+
+    let (removed, moved) = unsafe { archetype.swap_remove_unchecked::<(A, B)>(1) };
+    assert_eq!(removed, (A { _data: 1 }, B { _data: 1 }));
+    assert_eq!(moved, None);
+    assert_eq!(archetype.entities(), &[entities[2]]);
+}
+
+#[test]
+fn test_archetype_move_row_to() {
+    let mut source = Archetype::new(<(A, B) as ComponentGroup>::DESCRIPTOR.archetype());
+    let mut destination = Archetype::new(<(A, C) as ComponentGroup>::DESCRIPTOR.archetype());
+    let entities = (0..3)
+        .map(|e| unsafe { Entity::new_unchecked(e, 0) })
+        .collect::<alloc::vec::Vec<_>>();
+    for (e, entity) in entities.iter().enumerate() {
+        unsafe {
+            source.push_entity_unchecked(*entity, (A { _data: e }, B { _data: e }));
+        }
+    }
+
+    unsafe {
+        let (index, moved) = source.move_row_to(0, &mut destination);
+        destination.write_single_component_unchecked(index, C { _data: 10 });
+        assert_eq!(index, 0);
+        assert_eq!(moved, Some(entities[2]));
+        assert_eq!(source.entities(), &[entities[2], entities[1]]);
+        assert_eq!(source.get_component_unchecked::<B>(0), &B { _data: 2 });
+        assert_eq!(destination.entities(), &[entities[0]]);
+        assert_eq!(destination.get_component_unchecked::<A>(0), &A { _data: 0 });
+        assert_eq!(
+            destination.get_component_unchecked::<C>(0),
+            &C { _data: 10 }
+        );
+
+        let (index, moved) = source.move_row_to(1, &mut destination);
+        destination.write_single_component_unchecked(index, C { _data: 11 });
+        assert_eq!(index, 1);
+        assert_eq!(moved, None);
+        assert_eq!(source.entities(), &[entities[2]]);
+        assert_eq!(destination.entities(), &[entities[0], entities[1]]);
+        assert_eq!(destination.get_component_unchecked::<A>(1), &A { _data: 1 });
+    }
+}
+
+#[test]
+fn test_archetype_try_reserve() {
+    use crate::archetype::{AllocError, ColumnBackend, HeapBackend};
+    use alloc::alloc::Layout;
+
+    /// Fails to allocate columns larger than 64 bytes.
+    #[derive(Debug)]
+    struct LimitedBackend;
+
+    unsafe impl ColumnBackend for LimitedBackend {
+        unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+            if layout.size() > 64 {
+                return core::ptr::null_mut();
+            }
+            HeapBackend.allocate(layout)
+        }
+
+        unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            if new_size > 64 {
+                return core::ptr::null_mut();
+            }
+            HeapBackend.reallocate(ptr, layout, new_size)
+        }
+
+        unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+            HeapBackend.deallocate(ptr, layout)
+        }
+    }
+
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::with_capacity_in(descriptor, 2, &LimitedBackend);
+    let entities = unsafe { [Entity::from_raw(1), Entity::from_raw(2)] };
+    unsafe {
+        archetype.push_entity_unchecked(entities[0], (A { _data: 1 }, B { _data: 3 }));
+        archetype.push_entity_unchecked(entities[1], (A { _data: 2 }, B { _data: 4 }));
+    }
+    let elements_per_column = (64 / core::mem::size_of::<A>()) as u32;
+
+    assert_eq!(archetype.try_reserve(elements_per_column), Err(AllocError));
+    assert_eq!(archetype.capacity(), 2);
+    assert_eq!(archetype.try_reserve(elements_per_column - 2), Ok(()));
+    assert_eq!(archetype.capacity(), elements_per_column);
+    assert_eq!(archetype.try_reserve(1), Ok(()));
+    assert_eq!(archetype.capacity(), elements_per_column);
+    assert_eq!(archetype.entities(), &entities);
+    unsafe {
+        assert_eq!(archetype.get_component_unchecked::<A>(1), &A { _data: 2 });
+        assert_eq!(archetype.get_component_unchecked::<B>(0), &B { _data: 3 });
+    }
+
+    let mut empty = Archetype::with_capacity(descriptor, 0);
+    assert_eq!(empty.try_reserve(3), Ok(()));
+    assert_eq!(empty.capacity(), 3);
+}
+
+#[test]
+fn test_archetype_compact() {
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::new(descriptor);
+    unsafe {
+        for i in 0..6 {
+            archetype.push_entity_unchecked(
+                Entity::from_raw(i),
+                (A { _data: i as usize }, B { _data: 0 }),
+            );
+        }
+        // Punch holes into the odd rows.
+        let is_live = |index: u32| index.is_multiple_of(2);
+        for index in (0..6).filter(|index| !is_live(*index)) {
+            let _: (A, B) = archetype.read_components_exact_unchecked(index);
+        }
+        assert_eq!(archetype.compact(is_live), 3);
+        assert_eq!(archetype.len(), 3);
+        assert_eq!(
+            archetype.entities(),
+            &[
+                Entity::from_raw(0),
+                Entity::from_raw(2),
+                Entity::from_raw(4)
+            ]
+        );
+        for index in 0..3 {
+            assert_eq!(
+                archetype.get_component_unchecked::<A>(index),
+                &A {
+                    _data: index as usize * 2
+                }
+            );
+        }
+        assert_eq!(archetype.compact(|_| true), 0);
+        assert_eq!(archetype.len(), 3);
+    }
+}
+
+#[test]
+fn test_archetype_swap_entities_with_itself() {
+    unsafe {
+        let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype = Archetype::new(descriptor);
+        let entity = Entity::from_raw(1);
+        let index = archetype.push_entity_unchecked(entity, (A { _data: 1 }, B { _data: 3 }));
+        archetype.swap_entities(index, index);
+        assert_eq!(archetype.entities(), &[entity]);
+        assert_eq!(
+            *archetype.get_component_unchecked::<A>(index),
+            A { _data: 1 }
+        );
+        assert_eq!(
+            *archetype.get_component_unchecked::<B>(index),
+            B { _data: 3 }
+        );
+    }
+}
+
+#[test]
+fn test_archetype_grow_from_zero_capacity() {
+    unsafe {
+        let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype = Archetype::with_capacity(descriptor, 0);
+        assert_eq!(archetype.capacity(), 0);
+        let index =
+            archetype.push_entity_unchecked(Entity::from_raw(1), (A { _data: 1 }, B { _data: 2 }));
+        assert_eq!(archetype.capacity(), archetype.initial_capacity());
+        assert_eq!(
+            *archetype.get_component_unchecked::<B>(index),
+            B { _data: 2 }
+        );
+
+        let mut archetype = Archetype::with_capacity(descriptor, 0);
+        archetype.reserve(10);
+        assert!(archetype.capacity() >= 10);
+        archetype.push_entity_unchecked(Entity::from_raw(2), (A { _data: 3 }, B { _data: 4 }));
+        assert_eq!(archetype.column::<A>().unwrap(), &[A { _data: 3 }]);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Byte(u8);
+
+impl Component for Byte {
+    const NAME: &'static str = "Byte";
+}
+
+#[test]
+fn test_archetype_growth_clamped_to_max_entities() {
+    unsafe {
+        let descriptor = <Byte as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype =
+            Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE / 2 + 1);
+        archetype.push_entity_unchecked(Entity::from_raw(1), Byte(7));
+        // Pretend the archetype is full, the components are plain bytes.
+        archetype.entity_count = archetype.capacity();
+        archetype.push_entity_unchecked(Entity::from_raw(2), Byte(8));
+        assert_eq!(archetype.capacity(), MAX_ENTITIES_PER_ARCHETYPE - 1);
+        assert_eq!(*archetype.get_component_unchecked::<Byte>(0), Byte(7));
+        archetype.entity_count = 0;
+    }
+}
+
+#[test]
+#[should_panic(expected = "An archetype cannot store more than")]
+fn test_archetype_growth_beyond_max_entities_panics() {
+    unsafe {
+        let descriptor = <Byte as ComponentGroup>::DESCRIPTOR.archetype();
+        let mut archetype = Archetype::with_capacity(descriptor, MAX_ENTITIES_PER_ARCHETYPE - 1);
+        archetype.entity_count = archetype.capacity();
+        archetype.push_entity_unchecked(Entity::from_raw(1), Byte(0));
+    }
+}
+
+#[test]
+fn test_archetype_zero_sized_columns_skip_backend() {
+    use crate::archetype::{ColumnBackend, HeapBackend};
+    use alloc::alloc::Layout;
+
+    #[derive(Debug)]
+    struct NonZeroBackend;
+
+    unsafe impl ColumnBackend for NonZeroBackend {
+        unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+            assert_ne!(layout.size(), 0);
+            HeapBackend.allocate(layout)
+        }
+
+        unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            assert_ne!(layout.size(), 0);
+            assert_ne!(new_size, 0);
+            HeapBackend.reallocate(ptr, layout, new_size)
+        }
+
+        unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+            assert_ne!(layout.size(), 0);
+            HeapBackend.deallocate(ptr, layout)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Marker;
+    impl Component for Marker {
+        const NAME: &'static str = "Marker";
+    }
+
+    let descriptor = <(A, Marker) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::with_capacity_in(descriptor, 1, &NonZeroBackend);
+    unsafe {
+        for i in 0..10 {
+            archetype.push_entity_unchecked(Entity::from_raw(i), (A { _data: i as usize }, Marker));
+        }
+        assert_eq!(archetype.get_component_unchecked::<A>(9), &A { _data: 9 });
+        assert_eq!(archetype.get_component_unchecked::<Marker>(9), &Marker);
+    }
+    assert!(archetype.try_reserve(100).is_ok());
+    assert_eq!(archetype.column::<Marker>().unwrap().len(), 10);
+}
+
+#[cfg(all(feature = "mmap", unix))]
+#[test]
+fn test_archetype_mmap_backend() {
+    use crate::archetype::MmapBackend;
+
+    static BACKEND: MmapBackend = MmapBackend::new(c"/tmp");
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let mut archetype = Archetype::with_capacity_in(descriptor, 1, &BACKEND);
+    unsafe {
+        for i in 0..1000 {
+            archetype.push_entity_unchecked(
+                Entity::from_raw(i),
+                (A { _data: i as usize }, B { _data: 1 }),
+            );
+        }
+    }
+    assert!(archetype.capacity() >= 1000);
+    for (i, a) in archetype.column::<A>().unwrap().iter().enumerate() {
+        assert_eq!(a._data, i);
+    }
+    unsafe {
+        let (removed, _) = archetype.swap_remove_unchecked::<(A, B)>(0);
+        assert_eq!(removed.0, A { _data: 0 });
+        assert_eq!(archetype.get_component_unchecked::<A>(0), &A { _data: 999 });
+    }
+}
@@ -4,17 +4,18 @@ pub(crate) mod iterators;
 #[cfg(test)]
 mod tests;
 
+use alloc::collections::BTreeMap;
 use alloc::vec::*;
 use core::ops::{Index, IndexMut};
 use sorted_archetype_key::*;
 
-use crate::archetype::Archetype;
+use crate::archetype::{Archetype, ColumnBackend, HeapBackend};
 use crate::archetype_registry::iterators::*;
 use crate::constants::*;
 use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
 use crate::descriptors::component_descriptor::ComponentDescriptor;
 use crate::descriptors::component_group::ComponentGroup;
-use crate::Entity;
+use crate::{ArchetypeId, Entity, StableArchetypeId};
 
 const DEFAULT_VECTOR_CAPACITY: usize = 64;
 
@@ -40,28 +41,25 @@ fn disjoint_mut<T>(slice: &mut [T], first: usize, second: usize) -> Option<(&mut
 pub struct ArchetypeRegistry {
     // TODO: Currently not a great approach, should become a graph
     sorted_mappings: [Vec<SortedArchetypeKey>; MAX_COMPONENTS_PER_ENTITY],
+    // Initial capacities for archetypes which have not been created yet.
+    initial_capacities: BTreeMap<ArchetypeId, u32>,
+    // Column backends for archetypes which have not been created yet.
+    column_backends: BTreeMap<ArchetypeId, &'static dyn ColumnBackend>,
+    // Maps stable archetype ids to the current index of their archetype.
+    stable_indices: BTreeMap<StableArchetypeId, u16>,
+    next_stable_id: u32,
     archetypes: Vec<Archetype>,
 }
 
 impl Default for ArchetypeRegistry {
     fn default() -> Self {
         Self {
-            sorted_mappings: [
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-                Vec::with_capacity(DEFAULT_VECTOR_CAPACITY),
-            ],
+            // One bucket per amount of components, sized by the array type of the field.
+            sorted_mappings: core::array::from_fn(|_| Vec::with_capacity(DEFAULT_VECTOR_CAPACITY)),
+            initial_capacities: BTreeMap::new(),
+            column_backends: BTreeMap::new(),
+            stable_indices: BTreeMap::new(),
+            next_stable_id: 0,
             archetypes: Vec::with_capacity(128),
         }
     }
@@ -105,6 +103,46 @@ impl ArchetypeRegistry {
         }
     }
 
+    /// Returns the index of the archetype with the given archetype id, if it exists.
+    /// The id encodes the amount of components, so only archetypes with that amount are searched.
+    pub fn find_archetype_index_by_id(&self, archetype_id: ArchetypeId) -> Option<u16> {
+        let len = archetype_id.component_count() as usize;
+        if len == 0 || len > MAX_COMPONENTS_PER_ENTITY {
+            return None;
+        }
+        let mappings = &self.sorted_mappings[len - 1];
+        let found_index = mappings
+            .binary_search_by_key(&archetype_id, |e| e.id)
+            .ok()?;
+        Some(mappings[found_index].archetype_index)
+    }
+
+    /// Returns the index of the archetype with the given stable archetype id, if it exists.
+    pub fn find_archetype_index_by_stable_id(&self, stable_id: StableArchetypeId) -> Option<u16> {
+        self.stable_indices.get(&stable_id).copied()
+    }
+
+    /// Returns the archetype with the given stable archetype id, if it exists.
+    pub fn find_archetype_by_stable_id(&self, stable_id: StableArchetypeId) -> Option<&Archetype> {
+        let index = self.find_archetype_index_by_stable_id(stable_id)?;
+        Some(&self.archetypes[index as usize])
+    }
+
+    /// Returns the archetype with the given archetype id, if it exists.
+    pub fn find_archetype_by_id(&self, archetype_id: ArchetypeId) -> Option<&Archetype> {
+        let index = self.find_archetype_index_by_id(archetype_id)?;
+        Some(&self.archetypes[index as usize])
+    }
+
+    /// Returns a mutable reference to the archetype with the given archetype id, if it exists.
+    pub fn find_archetype_by_id_mut(
+        &mut self,
+        archetype_id: ArchetypeId,
+    ) -> Option<&mut Archetype> {
+        let index = self.find_archetype_index_by_id(archetype_id)?;
+        Some(&mut self.archetypes[index as usize])
+    }
+
     /// Returns mutable reference to source archetype and finds or creates a new archetype by adding
     /// the given component type as defined by component descriptor.
     pub fn find_or_create_archetype_adding_component(
@@ -113,7 +151,7 @@ impl ArchetypeRegistry {
         component_descriptor: &ComponentDescriptor,
     ) -> Option<(&mut Archetype, u16, &mut Archetype)> {
         // Range check
-        if source_archetype_index as usize > self.archetypes.len() {
+        if source_archetype_index as usize >= self.archetypes.len() {
             return None;
         }
         // create new archetype
@@ -121,6 +159,8 @@ impl ArchetypeRegistry {
             .descriptor()
             .add_component(component_descriptor)?;
 
+        // Creating the destination archetype may reallocate the archetype storage.
+        // Therefore both archetypes are only borrowed, by index, after it exists.
         let (destination_archetype_index, _) =
             self.find_or_create_archetype(&new_archetype_descriptor)?;
 
@@ -141,7 +181,7 @@ impl ArchetypeRegistry {
         component_descriptor: &ComponentDescriptor,
     ) -> Option<(&mut Archetype, u16, &mut Archetype)> {
         // Range check
-        if source_archetype_index as usize > self.archetypes.len() {
+        if source_archetype_index as usize >= self.archetypes.len() {
             return None;
         }
 
@@ -150,6 +190,52 @@ impl ArchetypeRegistry {
             .descriptor()
             .remove_component(component_descriptor.component_type_id())?;
 
+        // Creating the destination archetype may reallocate the archetype storage.
+        // Therefore both archetypes are only borrowed, by index, after it exists.
+        let (destination_archetype_index, _) =
+            self.find_or_create_archetype(&new_archetype_descriptor)?;
+
+        let (source, destination) = disjoint_mut(
+            &mut self.archetypes,
+            source_archetype_index as usize,
+            destination_archetype_index as usize,
+        )?;
+        Some((source, destination_archetype_index, destination))
+    }
+
+    /// Returns mutable reference to source archetype and finds or creates a new archetype by replacing
+    /// the removed component type by the added one. If the source archetype already contains the added
+    /// component type, the new archetype only lacks the removed one.
+    /// Returns None if the source archetype does not contain the removed component type.
+    pub fn find_or_create_archetype_replacing_component(
+        &mut self,
+        source_archetype_index: u16,
+        removed_component: &ComponentDescriptor,
+        added_component: &ComponentDescriptor,
+    ) -> Option<(&mut Archetype, u16, &mut Archetype)> {
+        let source_descriptor = self
+            .archetypes
+            .get(source_archetype_index as usize)?
+            .descriptor();
+        let contains = |descriptor: &ArchetypeDescriptor, component: &ComponentDescriptor| {
+            descriptor
+                .components()
+                .iter()
+                .any(|e| e.component_type_id() == component.component_type_id())
+        };
+        if !contains(source_descriptor, removed_component) {
+            return None;
+        }
+        let new_archetype_descriptor =
+            match source_descriptor.remove_component(removed_component.component_type_id()) {
+                Some(descriptor) if contains(&descriptor, added_component) => descriptor,
+                Some(descriptor) => descriptor.add_component(added_component)?,
+                // The removed component is the only one.
+                None => added_component.into(),
+            };
+
+        // Creating the destination archetype may reallocate the archetype storage.
+        // Therefore both archetypes are only borrowed, by index, after it exists.
         let (destination_archetype_index, _) =
             self.find_or_create_archetype(&new_archetype_descriptor)?;
 
@@ -161,9 +247,30 @@ impl ArchetypeRegistry {
         Some((source, destination_archetype_index, destination))
     }
 
+    /// Finds the archetype with the components of the descriptor, or creates it if it does not exist.
+    /// Returns None if the descriptor is invalid, the maximum amount of archetypes is reached,
+    /// or an archetype with different components has the same id.
     pub fn find_or_create_archetype(
         &mut self,
         archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Option<(u16, &mut Archetype)> {
+        self.find_or_create_archetype_with(archetype_descriptor, Archetype::with_capacity_in)
+    }
+
+    /// Finds or creates an archetype like [`ArchetypeRegistry::find_or_create_archetype`],
+    /// but does not allocate if the archetype is created.
+    pub fn find_or_create_unallocated_archetype(
+        &mut self,
+        archetype_descriptor: &ArchetypeDescriptor,
+    ) -> Option<(u16, &mut Archetype)> {
+        self.find_or_create_archetype_with(archetype_descriptor, Archetype::unallocated_in)
+    }
+
+    /// Finds an archetype, or creates it using [`create`] with its initial capacity and column backend.
+    fn find_or_create_archetype_with(
+        &mut self,
+        archetype_descriptor: &ArchetypeDescriptor,
+        create: fn(&ArchetypeDescriptor, u32, &'static dyn ColumnBackend) -> Archetype,
     ) -> Option<(u16, &mut Archetype)> {
         let len = archetype_descriptor.len() as usize;
         if len > MAX_COMPONENTS_PER_ENTITY || !archetype_descriptor.is_valid() {
@@ -172,20 +279,42 @@ impl ArchetypeRegistry {
         return match self.sorted_mappings[len - 1]
             .binary_search_by_key(&archetype_descriptor.archetype_id(), |e| e.id)
         {
-            Ok(found_index) => Some((
-                self.sorted_mappings[len - 1][found_index].archetype_index,
-                &mut self.archetypes
-                    [self.sorted_mappings[len - 1][found_index].archetype_index as usize],
-            )),
+            Ok(found_index) => {
+                let archetype_index = self.sorted_mappings[len - 1][found_index].archetype_index;
+                let archetype = &mut self.archetypes[archetype_index as usize];
+                // Ids are hashed, so a different set of components may have the same id.
+                let same_components = archetype
+                    .descriptor()
+                    .components()
+                    .iter()
+                    .map(|component| component.component_type_id())
+                    .eq(archetype_descriptor
+                        .components()
+                        .iter()
+                        .map(|component| component.component_type_id()));
+                if !same_components {
+                    return None;
+                }
+                Some((archetype_index, archetype))
+            }
             Err(insertion_index) => {
                 if self.archetypes.len() >= MAX_ARCHETYPE_COUNT {
                     return None;
                 }
-
-                let archetype = Archetype::with_capacity(
-                    archetype_descriptor,
-                    DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32,
-                );
+                let capacity = self
+                    .initial_capacities
+                    .remove(&archetype_descriptor.archetype_id())
+                    .unwrap_or(DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32);
+                let backend = self
+                    .column_backends
+                    .remove(&archetype_descriptor.archetype_id())
+                    .unwrap_or(&HeapBackend);
+                let mut archetype = create(archetype_descriptor, capacity, backend);
+                let stable_id = StableArchetypeId::from_u32(self.next_stable_id);
+                self.next_stable_id += 1;
+                archetype.set_stable_id(stable_id);
+                self.stable_indices
+                    .insert(stable_id, self.archetypes.len() as u16);
                 let key = SortedArchetypeKey {
                     id: archetype_descriptor.archetype_id(),
                     archetype_index: self.archetypes.len() as u16,
@@ -204,6 +333,7 @@ impl ArchetypeRegistry {
     /// # Safety
     /// Index must not be out of bounds.
     pub unsafe fn get_unchecked(&self, index: u16) -> &Archetype {
+        debug_assert!((index as usize) < self.archetypes.len());
         self.archetypes.get_unchecked(index as usize)
     }
 
@@ -211,9 +341,132 @@ impl ArchetypeRegistry {
     /// # Safety
     /// Index must not be out of bounds.
     pub unsafe fn get_unchecked_mut(&mut self, index: u16) -> &mut Archetype {
+        debug_assert!((index as usize) < self.archetypes.len());
         self.archetypes.get_unchecked_mut(index as usize)
     }
 
+    /// Sets the capacity used when the archetype with the given id is created.
+    /// Has no effect on the archetype if it already exists.
+    pub fn set_initial_capacity(&mut self, archetype_id: ArchetypeId, capacity: u32) {
+        if self.find_archetype_index_by_id(archetype_id).is_some() {
+            return;
+        }
+        self.initial_capacities.insert(archetype_id, capacity);
+    }
+
+    /// Sets the backend storing the component columns when the archetype with the given id is created.
+    /// Has no effect on the archetype if it already exists.
+    pub fn set_column_backend(
+        &mut self,
+        archetype_id: ArchetypeId,
+        backend: &'static dyn ColumnBackend,
+    ) {
+        if self.find_archetype_index_by_id(archetype_id).is_some() {
+            return;
+        }
+        self.column_backends.insert(archetype_id, backend);
+    }
+
+    /// Replaces the contents of the registry by clones of the archetypes in [`source`], keeping archetype indices.
+    /// Existing archetypes with the same id as an archetype in [`source`] are cleared and reused,
+    /// so their memory is only reallocated if their capacity is insufficient.
+    /// If a clone handler panics, the registry is left without entities.
+    /// # Safety
+    /// - Every component of every non-empty archetype in [`source`] must have a clone handler.
+    pub unsafe fn clone_from(&mut self, source: &ArchetypeRegistry) {
+        struct ClearOnUnwind<'a>(&'a mut Vec<Archetype>);
+        impl Drop for ClearOnUnwind<'_> {
+            fn drop(&mut self) {
+                self.0.iter_mut().for_each(Archetype::clear);
+            }
+        }
+
+        let mut reusable = BTreeMap::new();
+        for mut archetype in self.archetypes.drain(..) {
+            archetype.clear();
+            reusable.insert(archetype.descriptor().archetype_id(), archetype);
+        }
+        for source_archetype in &source.archetypes {
+            let descriptor = source_archetype.descriptor();
+            let mut archetype = reusable
+                .remove(&descriptor.archetype_id())
+                .unwrap_or_else(|| {
+                    Archetype::with_capacity_in(
+                        descriptor,
+                        source_archetype.capacity(),
+                        source_archetype.column_backend(),
+                    )
+                });
+            archetype.set_stable_id(source_archetype.stable_id());
+            archetype.mark_structural_change(source_archetype.last_structural_change());
+            archetype.mark_write(source_archetype.last_write());
+            self.archetypes.push(archetype);
+        }
+        self.sorted_mappings.clone_from(&source.sorted_mappings);
+        self.initial_capacities
+            .clone_from(&source.initial_capacities);
+        self.column_backends.clone_from(&source.column_backends);
+        self.stable_indices.clone_from(&source.stable_indices);
+        self.next_stable_id = source.next_stable_id;
+
+        // The registry is consistent at this point, only the rows are cloned from here on.
+        let guard = ClearOnUnwind(&mut self.archetypes);
+        for (archetype, source_archetype) in guard.0.iter_mut().zip(&source.archetypes) {
+            archetype.clone_rows_from(source_archetype);
+        }
+        core::mem::forget(guard);
+    }
+
+    /// Rebuilds the sorted mappings and the stable id index from the stored archetypes.
+    /// Restores consistent lookups after archetypes were added, removed or reordered in bulk.
+    pub fn rebuild_index(&mut self) {
+        self.sorted_mappings.iter_mut().for_each(Vec::clear);
+        self.stable_indices.clear();
+        for (index, archetype) in self.archetypes.iter().enumerate() {
+            let descriptor = archetype.descriptor();
+            self.sorted_mappings[descriptor.len() as usize - 1].push(SortedArchetypeKey {
+                id: descriptor.archetype_id(),
+                archetype_index: index as u16,
+            });
+            if archetype.stable_id() != StableArchetypeId::INVALID {
+                self.stable_indices
+                    .insert(archetype.stable_id(), index as u16);
+            }
+        }
+        for mappings in self.sorted_mappings.iter_mut() {
+            mappings.sort_unstable_by_key(|key| key.id);
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more archetypes.
+    /// Never reserves beyond [`MAX_ARCHETYPE_COUNT`] archetypes in total.
+    pub fn reserve_archetypes(&mut self, additional: usize) {
+        let additional = additional.min(MAX_ARCHETYPE_COUNT - self.archetypes.len());
+        self.archetypes.reserve(additional);
+    }
+
+    /// Returns an iterator over all archetypes in the registry.
+    pub fn iter_archetypes(&self) -> impl Iterator<Item = &Archetype> {
+        self.archetypes.iter()
+    }
+
+    /// Returns an iterator over all archetypes in the registry, allowing mutation.
+    pub fn iter_archetypes_mut(&mut self) -> impl Iterator<Item = &mut Archetype> {
+        self.archetypes.iter_mut()
+    }
+
+    pub fn iter_archetypes_matching<'a, G: ComponentGroup>(
+        &'a self,
+    ) -> impl Iterator<Item = &'a Archetype> {
+        ArchetypeIter::<'a, G>::new(&self.sorted_mappings, &self.archetypes)
+    }
+
+    pub fn iter_archetypes_matching_mut<'a, G: ComponentGroup>(
+        &'a mut self,
+    ) -> impl Iterator<Item = &'a mut Archetype> {
+        ArchetypeIterMut::<'a, G>::new(&self.sorted_mappings, &mut self.archetypes)
+    }
+
     pub fn iter_components_matching<'a, G: ComponentGroup>(
         &'a self,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceRefTuple<'a>> {
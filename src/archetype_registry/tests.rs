@@ -1,7 +1,10 @@
 use crate::archetype_registry::ArchetypeRegistry;
+use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+use crate::descriptors::component_descriptor::ComponentDescriptor;
 use crate::descriptors::component_group::ComponentGroup;
 use crate::entity_registry::*;
 use crate::test_components::*;
+use crate::{Component, ComponentTypeId, DEFAULT_ARCHETYPE_ALLOCATION_SIZE, MAX_ARCHETYPE_COUNT};
 
 #[test]
 fn test_archetype_registry() {
@@ -43,3 +46,273 @@ fn test_archetype_registry() {
         }
     }
 }
+
+/// Returns a descriptor for a component with the given id, laid out like [`A`].
+fn component_descriptor(id: u16) -> ComponentDescriptor {
+    ComponentDescriptor::new(
+        ComponentTypeId::from_u16(id),
+        <A as Component>::DESCRIPTOR.size(),
+        <A as Component>::DESCRIPTOR.align(),
+        <A as Component>::DESCRIPTOR.fns.drop_handler,
+    )
+}
+
+#[test]
+fn test_archetype_registry_find_by_id() {
+    let mut registry = ArchetypeRegistry::default();
+    let descriptors = (0..256)
+        .flat_map(|id| {
+            let single: ArchetypeDescriptor = component_descriptor(id).into();
+            let double = single
+                .add_component(&component_descriptor(id + 1000))
+                .unwrap();
+            [single, double]
+        })
+        .collect::<alloc::vec::Vec<_>>();
+
+    for descriptor in &descriptors {
+        let (index, _) = registry.find_or_create_archetype(descriptor).unwrap();
+        assert_eq!(
+            registry.find_archetype_index_by_id(descriptor.archetype_id()),
+            Some(index)
+        );
+    }
+    for descriptor in &descriptors {
+        let archetype = registry
+            .find_archetype_by_id(descriptor.archetype_id())
+            .unwrap();
+        assert_eq!(
+            archetype.descriptor().archetype_id(),
+            descriptor.archetype_id()
+        );
+        assert_eq!(archetype.descriptor().components(), descriptor.components());
+    }
+    assert!(registry
+        .find_archetype_by_id(
+            <(A, B) as ComponentGroup>::DESCRIPTOR
+                .archetype()
+                .archetype_id()
+        )
+        .is_none());
+}
+
+#[test]
+fn test_archetype_registry_reserve_archetypes() {
+    let mut registry = ArchetypeRegistry::default();
+    registry.reserve_archetypes(300);
+    assert!(registry.archetypes.capacity() >= 300);
+    registry.reserve_archetypes(usize::MAX);
+    assert!(registry.archetypes.capacity() >= MAX_ARCHETYPE_COUNT);
+}
+
+#[test]
+fn test_archetype_registry_adding_component_reallocates() {
+    let mut registry = ArchetypeRegistry::default();
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let (source_index, source) = registry.find_or_create_archetype(descriptor).unwrap();
+    unsafe {
+        source.push_entity_unchecked(Entity::INVALID, (A { _data: 7 }, B { _data: 8 }));
+    }
+    // Fill up the archetype storage, so creating the destination archetype must reallocate it.
+    let mut id = 1000;
+    while registry.archetypes.len() < registry.archetypes.capacity() {
+        registry
+            .find_or_create_archetype(&component_descriptor(id).into())
+            .unwrap();
+        id += 1;
+    }
+    let capacity = registry.archetypes.capacity();
+
+    let (source, _, destination) = registry
+        .find_or_create_archetype_adding_component(source_index, &<C as Component>::DESCRIPTOR)
+        .unwrap();
+    assert_eq!(
+        unsafe { source.get_component_unchecked::<A>(0) },
+        &A { _data: 7 }
+    );
+    assert!(destination.descriptor().has_component::<C>());
+    assert!(registry.archetypes.capacity() > capacity);
+}
+
+#[test]
+fn test_archetype_registry_set_initial_capacity() {
+    let mut registry = ArchetypeRegistry::default();
+    let small = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let large = <C as ComponentGroup>::DESCRIPTOR.archetype();
+    let default = <A as ComponentGroup>::DESCRIPTOR.archetype();
+    registry.set_initial_capacity(small.archetype_id(), 4);
+    registry.set_initial_capacity(large.archetype_id(), 1 << 20);
+
+    let (_, archetype) = registry.find_or_create_archetype(small).unwrap();
+    assert_eq!(archetype.capacity(), 4);
+    (0..5).for_each(|e| unsafe {
+        archetype.push_entity_unchecked(
+            Entity::new_unchecked(e, 0),
+            (A { _data: 0 }, B { _data: 0 }),
+        );
+    });
+    assert_eq!(archetype.capacity(), 8);
+    assert_eq!(archetype.initial_capacity(), 4);
+
+    let (_, archetype) = registry.find_or_create_archetype(large).unwrap();
+    assert_eq!(archetype.capacity(), 1 << 20);
+
+    let (_, archetype) = registry.find_or_create_archetype(default).unwrap();
+    assert_eq!(
+        archetype.capacity(),
+        DEFAULT_ARCHETYPE_ALLOCATION_SIZE as u32
+    );
+
+    // Existing archetypes are not affected.
+    registry.set_initial_capacity(small.archetype_id(), 16);
+    let (_, archetype) = registry.find_or_create_archetype(small).unwrap();
+    assert_eq!(archetype.initial_capacity(), 4);
+}
+
+#[test]
+fn test_archetype_registry_set_column_backend() {
+    use crate::archetype::{ColumnBackend, HeapBackend};
+    use alloc::alloc::Layout;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingBackend {
+        allocated_bytes: AtomicUsize,
+    }
+
+    unsafe impl ColumnBackend for CountingBackend {
+        unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+            self.allocated_bytes
+                .fetch_add(layout.size(), Ordering::Relaxed);
+            HeapBackend.allocate(layout)
+        }
+
+        unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            self.allocated_bytes.fetch_add(new_size, Ordering::Relaxed);
+            self.allocated_bytes
+                .fetch_sub(layout.size(), Ordering::Relaxed);
+            HeapBackend.reallocate(ptr, layout, new_size)
+        }
+
+        unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+            self.allocated_bytes
+                .fetch_sub(layout.size(), Ordering::Relaxed);
+            HeapBackend.deallocate(ptr, layout)
+        }
+    }
+
+    static BACKEND: CountingBackend = CountingBackend {
+        allocated_bytes: AtomicUsize::new(0),
+    };
+
+    let mut registry = ArchetypeRegistry::default();
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    registry.set_column_backend(descriptor.archetype_id(), &BACKEND);
+    registry.set_initial_capacity(descriptor.archetype_id(), 2);
+    let (_, archetype) = registry.find_or_create_archetype(descriptor).unwrap();
+    let column_size = core::mem::size_of::<A>() + core::mem::size_of::<B>();
+    assert_eq!(
+        BACKEND.allocated_bytes.load(Ordering::Relaxed),
+        2 * column_size
+    );
+    (0..3).for_each(|e| unsafe {
+        archetype.push_entity_unchecked(
+            Entity::new_unchecked(e, 0),
+            (A { _data: 0 }, B { _data: 0 }),
+        );
+    });
+    assert_eq!(
+        BACKEND.allocated_bytes.load(Ordering::Relaxed),
+        4 * column_size
+    );
+
+    // Other archetypes keep using the heap.
+    let (_, _) = registry
+        .find_or_create_archetype(<A as ComponentGroup>::DESCRIPTOR.archetype())
+        .unwrap();
+    assert_eq!(
+        BACKEND.allocated_bytes.load(Ordering::Relaxed),
+        4 * column_size
+    );
+
+    drop(registry);
+    assert_eq!(BACKEND.allocated_bytes.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn test_archetype_registry_rebuild_index() {
+    let mut registry = ArchetypeRegistry::default();
+    let descriptors = [
+        <A as ComponentGroup>::DESCRIPTOR.archetype(),
+        <(A, B) as ComponentGroup>::DESCRIPTOR.archetype(),
+        <(B, C) as ComponentGroup>::DESCRIPTOR.archetype(),
+        <(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype(),
+    ];
+    for descriptor in descriptors {
+        registry.find_or_create_archetype(descriptor).unwrap();
+    }
+    let stable_ids =
+        descriptors.map(|descriptor| registry.find_archetype(descriptor).unwrap().stable_id());
+
+    // Corrupt the index by dropping and reversing the mappings.
+    registry.sorted_mappings[0].clear();
+    registry.sorted_mappings[1].reverse();
+    registry.stable_indices.clear();
+    assert!(registry.find_archetype(descriptors[0]).is_none());
+
+    registry.rebuild_index();
+    for (index, descriptor) in descriptors.iter().enumerate() {
+        let archetype = registry.find_archetype(descriptor).unwrap();
+        assert_eq!(
+            archetype.descriptor().archetype_id(),
+            descriptor.archetype_id()
+        );
+        assert_eq!(
+            registry.find_archetype_index_by_id(descriptor.archetype_id()),
+            Some(index as u16)
+        );
+        assert_eq!(
+            registry.find_archetype_index_by_stable_id(stable_ids[index]),
+            Some(index as u16)
+        );
+    }
+}
+
+#[test]
+fn test_archetype_registry_out_of_range_source_index() {
+    let mut registry = ArchetypeRegistry::default();
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let (index, _) = registry.find_or_create_archetype(descriptor).unwrap();
+    let len = registry.iter_archetypes().count() as u16;
+    assert_eq!(index + 1, len);
+    assert!(registry
+        .find_or_create_archetype_adding_component(len, &<C as Component>::DESCRIPTOR)
+        .is_none());
+    assert!(registry
+        .find_or_create_archetype_removing_component(len, &<A as Component>::DESCRIPTOR)
+        .is_none());
+    assert!(registry
+        .find_or_create_archetype_adding_component(index, &<C as Component>::DESCRIPTOR)
+        .is_some());
+}
+
+#[test]
+fn test_archetype_registry_colliding_archetype_id() {
+    let mut registry = ArchetypeRegistry::default();
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    let (index, _) = registry.find_or_create_archetype(descriptor).unwrap();
+
+    // Different components with the same arity and id, as if their hashes collided.
+    let mut components = [ComponentDescriptor::INVALID; crate::MAX_COMPONENTS_PER_ENTITY];
+    components[0] = <A as Component>::DESCRIPTOR;
+    components[1] = <C as Component>::DESCRIPTOR;
+    let colliding = ArchetypeDescriptor::new(descriptor.archetype_id(), 2, components);
+    assert!(registry.find_or_create_archetype(&colliding).is_none());
+    assert!(registry
+        .find_or_create_unallocated_archetype(&colliding)
+        .is_none());
+
+    let (found, archetype) = registry.find_or_create_archetype(descriptor).unwrap();
+    assert_eq!(found, index);
+    assert_eq!(archetype.descriptor().components(), descriptor.components());
+}
@@ -18,7 +18,7 @@ pub const ENTITY_VERSION_BITS: u8 = 8;
 pub const MAX_ENTITY_VERSION_VALUE: u8 = 255;
 
 /// The maximum number of components an entity is allowed to have.
-pub const MAX_COMPONENTS_PER_ENTITY: usize = 14;
+pub const MAX_COMPONENTS_PER_ENTITY: usize = 16;
 
 /// The maximum amount of unique archetypes that a registry can contain.
 pub const MAX_ARCHETYPE_COUNT: usize = (u16::MAX - 1) as usize;
@@ -1,4 +1,5 @@
 use crate::descriptors::component_descriptor::ComponentDescriptor;
+use crate::descriptors::component_group::ComponentGroup;
 use crate::fnv1a::fnv1a_hash_32;
 use crate::{constants::*, ArchetypeId, Component, ComponentTypeId};
 
@@ -50,13 +51,27 @@ impl ArchetypeDescriptor {
         if descriptors.is_empty() {
             return ArchetypeId::INVALID;
         }
-        if descriptors.len() == 1 {
-            return ArchetypeId::from_u32(descriptors[0].component_type_id().into_u16() as u32);
+        // Padding with invalid descriptors does not count as components.
+        let mut component_count = 0;
+        while component_count < descriptors.len()
+            && descriptors[component_count].component_type_id().is_valid()
+        {
+            component_count += 1;
+        }
+        if component_count == 0 || component_count > MAX_COMPONENTS_PER_ENTITY {
+            return ArchetypeId::INVALID;
+        }
+        if component_count == 1 {
+            return ArchetypeId::from_parts(
+                1,
+                descriptors[0].component_type_id().into_u16() as u32,
+            );
         }
 
+        // Only the components are hashed, so padded and unpadded descriptors result in the same id.
         let mut bytes = [0; MAX_COMPONENTS_PER_ENTITY * core::mem::size_of::<ComponentTypeId>()];
         let mut i = 0;
-        while i < descriptors.len() {
+        while i < component_count {
             let byte_block = ComponentTypeId::to_ne_bytes(descriptors[i].component_type_id());
             let mut j = 0;
             while j < core::mem::size_of::<ComponentTypeId>() {
@@ -65,7 +80,10 @@ impl ArchetypeDescriptor {
             }
             i += 1;
         }
-        ArchetypeId::from_u32(fnv1a_hash_32(&bytes, Some(bytes.len())))
+        ArchetypeId::from_parts(
+            component_count as u8,
+            fnv1a_hash_32(&bytes, Some(bytes.len())),
+        )
     }
 
     /// Returns whether the descriptor provided is contained in self. (i.e. subset inclusion)
@@ -114,6 +132,35 @@ impl ArchetypeDescriptor {
         true
     }
 
+    /// Creates an archetype descriptor from component descriptors sorted by component type id.
+    /// Returns None if the descriptors are not sorted, contain duplicates,
+    /// or their amount is zero or exceeds [`MAX_COMPONENTS_PER_ENTITY`].
+    pub fn from_sorted_components(
+        components: &[ComponentDescriptor],
+    ) -> Option<ArchetypeDescriptor> {
+        if components.is_empty() || components.len() > MAX_COMPONENTS_PER_ENTITY {
+            return None;
+        }
+        if components
+            .windows(2)
+            .any(|pair| pair[0].component_type_id >= pair[1].component_type_id)
+        {
+            return None;
+        }
+        if components.len() == 1 {
+            return Some((&components[0]).into());
+        }
+        let mut descriptors = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
+        descriptors[0..components.len()].clone_from_slice(components);
+        // Computed the same way as for component groups, so both describe the same archetype.
+        let id = ArchetypeDescriptor::compute_archetype_id(&descriptors);
+        Some(ArchetypeDescriptor::new(
+            id,
+            components.len() as u8,
+            descriptors,
+        ))
+    }
+
     /// Returns a new archetype with the given component type added to it.
     /// Returns none if the current archetype already contains the component type or it is full.
     #[allow(dead_code)]
@@ -137,7 +184,7 @@ impl ArchetypeDescriptor {
             Ok(_) => None, // Current archetype already contains given component.
             Err(insertion_index) => {
                 let mut v = self.clone();
-                for i in insertion_index..self.len() as usize + 1 {
+                for i in insertion_index..self.len() as usize {
                     v.components[i + 1] = self.components[i].clone();
                 }
                 v.components[insertion_index] = component_descriptor.clone();
@@ -150,8 +197,12 @@ impl ArchetypeDescriptor {
         }
     }
 
+    /// Returns a new archetype with the given component type removed from it.
+    /// The remaining components stay sorted and the archetype id is recomputed.
+    /// Returns none if the current archetype does not contain the component type
+    /// or if it is the only component, as archetypes cannot contain zero components.
     pub fn remove_component(&self, component: ComponentTypeId) -> Option<ArchetypeDescriptor> {
-        if self.len() as usize == 1 {
+        if self.len() as usize <= 1 {
             return None; // Archetype cannot contain zero components.
         }
         match self.components[0..self.len() as usize]
@@ -159,9 +210,10 @@ impl ArchetypeDescriptor {
         {
             Ok(found_index) => {
                 let mut v = self.clone();
-                for i in found_index..self.len() as usize {
+                for i in found_index..self.len() as usize - 1 {
                     v.components[i] = self.components[i + 1].clone();
                 }
+                v.components[self.len() as usize - 1] = ComponentDescriptor::INVALID;
 
                 v.len -= 1;
                 v.archetype_id =
@@ -180,6 +232,18 @@ impl ArchetypeDescriptor {
             .is_ok();
     }
 
+    /// Returns whether the archetype descriptor has the given component type.
+    /// Scans the sorted components linearly, which is faster than a binary search for the few
+    /// components archetypes usually have.
+    pub fn contains_component_type(&self, component_type_id: ComponentTypeId) -> bool {
+        for component in self.components() {
+            if component.component_type_id >= component_type_id {
+                return component.component_type_id == component_type_id;
+            }
+        }
+        false
+    }
+
     /// Get a the archetype descriptor's archetype id.
     pub const fn archetype_id(&self) -> ArchetypeId {
         self.archetype_id
@@ -207,9 +271,33 @@ impl ArchetypeDescriptor {
     }
 }
 
+/// Panics in debug builds if the dynamically constructed descriptor does not describe
+/// the same archetype as component group [`G`], i.e. if their archetype ids or components differ.
+pub fn debug_assert_descriptors_match<G: ComponentGroup>(dynamic: &ArchetypeDescriptor) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let expected = G::DESCRIPTOR.archetype();
+    assert_eq!(
+        dynamic.archetype_id(),
+        expected.archetype_id(),
+        "The dynamic descriptor has a different archetype id than the component group."
+    );
+    assert!(
+        dynamic
+            .components()
+            .iter()
+            .map(|component| component.component_type_id())
+            .eq(expected.components().iter().map(|c| c.component_type_id())),
+        "The dynamic descriptor has different components than the component group."
+    );
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
+    use crate::descriptors::archetype_descriptor::{
+        debug_assert_descriptors_match, ArchetypeDescriptor,
+    };
     use crate::descriptors::component_group::ComponentGroup;
     use crate::test_components::*;
     use crate::Component;
@@ -232,6 +320,38 @@ mod tests {
         assert_eq!(descriptor.len(), 2);
     }
 
+    #[test]
+    fn test_archetype_descriptor_remove_middle_component() {
+        let descriptor = <(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype();
+        let middle = descriptor.components()[1].component_type_id;
+        let removed = descriptor.remove_component(middle).unwrap();
+        assert!(removed.is_valid());
+        assert_eq!(removed.len(), 2);
+        assert_eq!(removed.components()[0], descriptor.components()[0]);
+        assert_eq!(removed.components()[1], descriptor.components()[2]);
+        let remaining = [
+            descriptor.components()[0].clone(),
+            descriptor.components()[2].clone(),
+        ];
+        assert_eq!(
+            removed.archetype_id(),
+            ArchetypeDescriptor::compute_archetype_id(&remaining)
+        );
+        assert_eq!(
+            removed.archetype_id(),
+            ArchetypeDescriptor::from_sorted_components(&remaining)
+                .unwrap()
+                .archetype_id()
+        );
+
+        assert!(removed.remove_component(middle).is_none());
+        let single = <A as ComponentGroup>::DESCRIPTOR.archetype();
+        assert!(single.remove_component(A::ID).is_none());
+        assert!(ArchetypeDescriptor::INVALID
+            .remove_component(A::ID)
+            .is_none());
+    }
+
     #[test]
     fn test_archetype_descriptor_contains() {
         assert_eq!(
@@ -275,4 +395,64 @@ mod tests {
             true
         );
     }
+
+    #[test]
+    fn test_archetype_id_component_count() {
+        assert_eq!(
+            <A as ComponentGroup>::DESCRIPTOR
+                .archetype()
+                .archetype_id()
+                .component_count(),
+            1
+        );
+        let descriptor = <(A, B, C) as ComponentGroup>::DESCRIPTOR.archetype();
+        assert_eq!(descriptor.archetype_id().component_count(), 3);
+        let descriptor = descriptor.remove_component(C::ID).unwrap();
+        assert_eq!(descriptor.archetype_id().component_count(), 2);
+        assert_eq!(crate::ArchetypeId::INVALID.component_count(), 0);
+        assert!(!ArchetypeDescriptor::compute_archetype_id(&[]).is_valid());
+    }
+
+    #[test]
+    fn test_archetype_descriptor_dynamic_matches_static() {
+        let descriptor = <A as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .add_component(&<C as Component>::DESCRIPTOR)
+            .unwrap();
+        debug_assert_descriptors_match::<(A, C)>(&descriptor);
+        let descriptor = descriptor
+            .add_component(&<B as Component>::DESCRIPTOR)
+            .unwrap();
+        debug_assert_descriptors_match::<(C, B, A)>(&descriptor);
+        let descriptor = descriptor.remove_component(C::ID).unwrap();
+        debug_assert_descriptors_match::<(A, B)>(&descriptor);
+        let descriptor = descriptor.remove_component(A::ID).unwrap();
+        debug_assert_descriptors_match::<B>(&descriptor);
+
+        let components = [
+            <A as Component>::DESCRIPTOR,
+            <B as Component>::DESCRIPTOR,
+            <C as Component>::DESCRIPTOR,
+        ];
+        let descriptor = ArchetypeDescriptor::from_sorted_components(&components).unwrap();
+        debug_assert_descriptors_match::<(A, B, C)>(&descriptor);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_archetype_descriptor_dynamic_mismatch() {
+        let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+        debug_assert_descriptors_match::<(A, C)>(descriptor);
+    }
+
+    #[test]
+    fn test_archetype_descriptor_contains_component_type() {
+        let descriptor = <(A, C) as ComponentGroup>::DESCRIPTOR.archetype();
+        assert!(descriptor.contains_component_type(A::ID));
+        assert!(!descriptor.contains_component_type(B::ID));
+        assert!(descriptor.contains_component_type(C::ID));
+        assert!(!descriptor.contains_component_type(DropLogA::ID));
+        assert!(!ArchetypeDescriptor::INVALID.contains_component_type(A::ID));
+    }
 }
@@ -1,6 +1,8 @@
 /// Represents the unique subset of components as a comparable identifier.
 /// See [`ArchetypeDescriptor::compute_archetype_id`] for computing an instance of it.
-/// The invalid archetype id is defined to be 0.
+/// The upper 5 bits store the amount of components, the lower 27 bits identify the components.
+/// Archetypes with a different amount of components therefore never share an id.
+/// The invalid archetype id is defined to be [`u32::MAX`].
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ArchetypeId(u32);
@@ -27,6 +29,25 @@ impl ArchetypeId {
     pub const fn into_u32(self) -> u32 {
         self.0
     }
+    /// Construct an archetype id from the amount of components and an identifier of the components.
+    /// Only the lower 27 bits of the identifier are used.
+    pub const fn from_parts(component_count: u8, identifier: u32) -> Self {
+        ArchetypeId(
+            ((component_count as u32) << Self::COMPONENT_COUNT_SHIFT)
+                | (identifier & Self::IDENTIFIER_MASK),
+        )
+    }
+    /// Returns the amount of components in the archetype with this id.
+    /// Returns 0 if the id is invalid.
+    pub const fn component_count(&self) -> u8 {
+        if !self.is_valid() {
+            return 0;
+        }
+        (self.0 >> Self::COMPONENT_COUNT_SHIFT) as u8
+    }
+
+    const COMPONENT_COUNT_SHIFT: u32 = 27;
+    const IDENTIFIER_MASK: u32 = (1 << Self::COMPONENT_COUNT_SHIFT) - 1;
 }
 
 impl ArchetypeId {
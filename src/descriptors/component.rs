@@ -11,6 +11,18 @@ use crate::{define_component_descriptor, fnv1a::fnv1a_hash_str_16_xor};
 pub trait Component: Send + Sync + Sized + 'static {
     /// Human readable program unique name used for calculating a stable type identifier.
     const NAME: &'static str;
+    /// Alignment of the memory storing all instances of this component in an archetype.
+    /// Defaults to 0, which means the natural alignment of the type is used.
+    /// Must be a power of two and at least the natural alignment, e.g. 64 to align a column to cache lines.
+    const COLUMN_ALIGN: usize = 0;
+    /// Type erased equality used to detect writes which do not change a component.
+    /// Defaults to None, which means every write is considered a change.
+    /// Set it to `Some(ComponentDescriptor::eq_handler_wrapper::<Self>)` for components implementing [`PartialEq`].
+    const EQ_HANDLER: Option<unsafe fn(*const u8, *const u8) -> bool> = None;
+    /// Type erased clone used by [`crate::Registry::clone_from`].
+    /// Defaults to None, which means registries storing this component cannot be cloned.
+    /// Set it to `Some(ComponentDescriptor::clone_handler_wrapper::<Self>)` for components implementing [`Clone`].
+    const CLONE_HANDLER: Option<unsafe fn(*const u8, *mut u8, usize)> = None;
     /// Do not implement this manually. (Unless a hash collision occurs).
     const ID: ComponentTypeId = ComponentTypeId::from_u16(fnv1a_hash_str_16_xor(Self::NAME));
     /// A descriptor defining the component type.
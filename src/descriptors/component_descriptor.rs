@@ -2,7 +2,7 @@ use core::mem::ManuallyDrop;
 
 use crate::descriptors::archetype_descriptor::ArchetypeDescriptor;
 use crate::descriptors::component_type_id::ComponentTypeId;
-use crate::Component;
+use crate::{Component, MAX_COMPONENTS_PER_ENTITY};
 
 #[macro_export]
 macro_rules! define_component_descriptor {
@@ -11,8 +11,11 @@ macro_rules! define_component_descriptor {
             component_type_id: $item::ID,
             size: core::mem::size_of::<$item>() as u16,
             align: core::mem::align_of::<$item>() as u16,
+            column_align: ComponentDescriptor::column_align_of::<$item>(),
             fns: ComponentDescriptorFnPointers {
                 drop_handler: ComponentDescriptor::drop_handler_wrapper::<$item>,
+                eq_handler: $item::EQ_HANDLER,
+                clone_handler: $item::CLONE_HANDLER,
             },
         }
     };
@@ -24,75 +27,60 @@ macro_rules! copy_component_descriptor_from_to {
         $destination.component_type_id = $source.component_type_id;
         $destination.size = $source.size;
         $destination.align = $source.align;
+        $destination.column_align = $source.column_align;
         $destination.fns = $source.fns;
     };
 }
 
 /// Groups special function pointers used for memory operations on component instances.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Function pointers cannot be compared meaningfully, so these are not compared.
+#[derive(Debug, Clone, Copy)]
 pub struct ComponentDescriptorFnPointers {
     pub drop_handler: unsafe fn(ptr: *mut u8, len: usize),
+    /// Compares two component instances, None if the component does not provide equality.
+    pub eq_handler: Option<unsafe fn(lhs: *const u8, rhs: *const u8) -> bool>,
+    /// Clones `len` component instances into uninitialized memory, None if the component cannot be cloned.
+    pub clone_handler: Option<unsafe fn(source: *const u8, destination: *mut u8, len: usize)>,
 }
 
 /// Describes a specific component type.
 /// # Safety:
 /// - [`size`] must not exceed [`u16::MAX`].
 /// - [`align`] must not exceed [`u16::MAX`].
-#[derive(Debug, Clone, PartialEq)]
+/// - [`column_align`] must be a power of two and at least [`align`].
+#[derive(Debug, Clone)]
 pub struct ComponentDescriptor {
     pub component_type_id: ComponentTypeId,
     pub size: u16,
     pub align: u16,
+    pub column_align: u16,
     pub fns: ComponentDescriptorFnPointers,
 }
 
+/// Descriptors are equal if they describe the same component type.
+impl PartialEq for ComponentDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.component_type_id == other.component_type_id
+    }
+}
+
 impl Into<ArchetypeDescriptor> for &ComponentDescriptor {
     fn into(self) -> ArchetypeDescriptor {
-        ArchetypeDescriptor::new(
-            self.component_type_id.into(),
-            1,
-            [
-                self.clone(),
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-            ],
-        )
+        ArchetypeDescriptor::new(self.component_type_id.into(), 1, {
+            let mut components = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
+            components[0] = self.clone();
+            components
+        })
     }
 }
 
 impl Into<ArchetypeDescriptor> for ComponentDescriptor {
     fn into(self) -> ArchetypeDescriptor {
-        ArchetypeDescriptor::new(
-            self.component_type_id.into(),
-            1,
-            [
-                self,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-                ComponentDescriptor::INVALID,
-            ],
-        )
+        ArchetypeDescriptor::new(self.component_type_id.into(), 1, {
+            let mut components = [ComponentDescriptor::INVALID; MAX_COMPONENTS_PER_ENTITY];
+            components[0] = self;
+            components
+        })
     }
 }
 
@@ -105,13 +93,17 @@ impl ComponentDescriptor {
             component_type_id: ComponentTypeId::INVALID,
             size: 0,
             align: 0,
+            column_align: 0,
             fns: ComponentDescriptorFnPointers {
                 drop_handler: _dummy_drop_,
+                eq_handler: None,
+                clone_handler: None,
             },
         }
     };
 
     /// Creates a new component descriptor from the provided arguments.
+    /// The column alignment is the same as [`align`] and no equality or clone handler is set.
     /// Returns [`ComponentDescriptor::INVALID`] if a valid descriptor cannot be constructed.
     pub fn new(
         component_type_id: ComponentTypeId,
@@ -127,8 +119,32 @@ impl ComponentDescriptor {
             component_type_id,
             size,
             align,
-            fns: ComponentDescriptorFnPointers { drop_handler },
+            column_align: align,
+            fns: ComponentDescriptorFnPointers {
+                drop_handler,
+                eq_handler: None,
+                clone_handler: None,
+            },
+        }
+    }
+
+    /// Do not use this manually. Returns the alignment of the column storing component [`C`].
+    /// Panics if [`Component::COLUMN_ALIGN`] is not a power of two or below the natural alignment.
+    pub const fn column_align_of<C: Component>() -> u16 {
+        let natural_align = core::mem::align_of::<C>();
+        if C::COLUMN_ALIGN == 0 {
+            return natural_align as u16;
+        }
+        if !C::COLUMN_ALIGN.is_power_of_two() {
+            panic!("The column alignment of a component must be a power of two.");
+        }
+        if C::COLUMN_ALIGN < natural_align {
+            panic!("The column alignment of a component must be at least its natural alignment.");
         }
+        if C::COLUMN_ALIGN > u16::MAX as usize {
+            panic!("The column alignment of a component must not exceed u16::MAX.");
+        }
+        C::COLUMN_ALIGN as u16
     }
 
     /// Do not use this manually. It wraps a type erased drop handler.
@@ -139,6 +155,33 @@ impl ComponentDescriptor {
         s.iter_mut().for_each(|e| ManuallyDrop::drop(e))
     }
 
+    /// Wraps [`PartialEq`] of component [`C`] into a type erased equality handler.
+    /// Use it to implement [`Component::EQ_HANDLER`].
+    /// # Safety
+    /// Both pointers must be properly aligned and point to valid instances of C.
+    pub unsafe fn eq_handler_wrapper<C: Component + PartialEq>(
+        lhs: *const u8,
+        rhs: *const u8,
+    ) -> bool {
+        *(lhs as *const C) == *(rhs as *const C)
+    }
+
+    /// Wraps [`Clone`] of component [`C`] into a type erased clone handler.
+    /// Use it to implement [`Component::CLONE_HANDLER`].
+    /// # Safety
+    /// - `source` must point to `len` valid instances of C.
+    /// - `destination` must be properly aligned and valid for writing `len` instances of C.
+    pub unsafe fn clone_handler_wrapper<C: Component + Clone>(
+        source: *const u8,
+        destination: *mut u8,
+        len: usize,
+    ) {
+        let source = core::slice::from_raw_parts(source as *const C, len);
+        for (index, component) in source.iter().enumerate() {
+            (destination as *mut C).add(index).write(component.clone());
+        }
+    }
+
     /// Get a the component descriptor's component type id.
     pub const fn component_type_id(&self) -> ComponentTypeId {
         self.component_type_id
@@ -153,4 +196,9 @@ impl ComponentDescriptor {
     pub const fn align(&self) -> u16 {
         self.align
     }
+
+    /// Get a the component descriptor's column align.
+    pub const fn column_align(&self) -> u16 {
+        self.column_align
+    }
 }
@@ -20,6 +20,32 @@ macro_rules! tuple_index {
     };
 }
 
+/// Panics in debug builds if any of the sorted pointers of the group alias each other.
+/// Handing out multiple mutable references to the same component column is undefined behaviour.
+/// Pointers to zero sized components are skipped, as these cannot alias any data.
+#[inline(always)]
+fn debug_assert_disjoint_pointers(
+    descriptor: &ComponentGroupDescriptor,
+    sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
+) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let components = descriptor.archetype().components();
+    for (i, first) in components.iter().enumerate() {
+        for (j, second) in components.iter().enumerate().skip(i + 1) {
+            if first.size() == 0 || second.size() == 0 {
+                continue;
+            }
+            assert_ne!(
+                sorted_pointers[i], sorted_pointers[j],
+                "Component {:?} aliases the column of component {:?}.",
+                second.component_type_id, first.component_type_id
+            );
+        }
+    }
+}
+
 /// Represents a group of components. Used for specifying which component types should be matched in query's.
 pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
     type RefTuple<'c>: 'c
@@ -38,6 +64,14 @@ pub trait ComponentGroup: SealedComponentGroup + Sized + 'static {
     /// The descriptor which exactly specifies all components of the component group.
     const DESCRIPTOR: ComponentGroupDescriptor;
 
+    /// The amount of components in the component group.
+    const LEN: usize = Self::DESCRIPTOR.len() as usize;
+
+    /// Returns the descriptors of all components in the component group, sorted by component type id.
+    fn descriptors() -> &'static [ComponentDescriptor] {
+        const { &Self::DESCRIPTOR }.archetype().components()
+    }
+
     /// Returns the sorted pointers given a reference to self.
     unsafe fn as_sorted_pointers(&mut self, ptrs: &mut [*mut u8; MAX_COMPONENTS_PER_ENTITY]);
 
@@ -164,24 +198,25 @@ macro_rules! impl_component_tuple {
                 ),*)
             }
 
-            unsafe fn pointers_as_ref_tuple<'a, 'b>(
-                sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+            unsafe fn pointers_as_ref_tuple<'b>(
+                sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
             ) -> Self::RefTuple<'b> {
                 ($(
                     &*((sorted_pointers[Self::DESCRIPTOR.unsorted_to_sorted($elem_idx) as usize]) as *mut $elem),
                 )*)
             }
 
-            unsafe fn pointers_as_mut_ref_tuple<'a, 'b>(
-                sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+            unsafe fn pointers_as_mut_ref_tuple<'b>(
+                sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
             ) -> Self::MutRefTuple<'b> {
+                debug_assert_disjoint_pointers(&Self::DESCRIPTOR, sorted_pointers);
                 ($(
                     &mut *((sorted_pointers[Self::DESCRIPTOR.unsorted_to_sorted($elem_idx) as usize]) as *mut $elem),
                 )*)
             }
 
-            unsafe fn slice_unchecked<'a, 'b>(
-                sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+            unsafe fn slice_unchecked<'b>(
+                sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
                 len: usize,
             ) -> Self::SliceRefTuple<'b> {
                 ($(
@@ -189,11 +224,12 @@ macro_rules! impl_component_tuple {
                 )*)
             }
 
-            unsafe fn slice_unchecked_mut<'a, 'b>(
-                sorted_pointers: &'a [*mut u8; MAX_COMPONENTS_PER_ENTITY],
+            unsafe fn slice_unchecked_mut<'b>(
+                sorted_pointers: &[*mut u8; MAX_COMPONENTS_PER_ENTITY],
                 len: usize,
             ) -> Self::SliceMutRefTuple<'b> {
-                                ($(
+                debug_assert_disjoint_pointers(&Self::DESCRIPTOR, sorted_pointers);
+                ($(
                     core::slice::from_raw_parts_mut(sorted_pointers[Self::DESCRIPTOR.unsorted_to_sorted($elem_idx) as usize] as *mut $elem, len),
                 )*)
             }
@@ -227,6 +263,57 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_component_group_as_sorted_pointers_16() {
+        unsafe {
+            let mut group = (
+                W16(16),
+                W15(15),
+                W14(14),
+                W13(13),
+                W12(12),
+                W11(11),
+                W10(10),
+                W9(9),
+                W8(8),
+                W7(7),
+                W6(6),
+                W5(5),
+                W4(4),
+                W3(3),
+                W2(2),
+                W1(1),
+            );
+            let mut ptrs = [core::ptr::null_mut(); MAX_COMPONENTS_PER_ENTITY];
+            ComponentGroup::as_sorted_pointers(&mut group, &mut ptrs);
+            assert_eq!(ptrs[0], &mut group.15 as *mut W1 as *mut u8);
+            assert_eq!(ptrs[7], &mut group.8 as *mut W8 as *mut u8);
+            assert_eq!(ptrs[15], &mut group.0 as *mut W16 as *mut u8);
+
+            let read = <(
+                W16,
+                W15,
+                W14,
+                W13,
+                W12,
+                W11,
+                W10,
+                W9,
+                W8,
+                W7,
+                W6,
+                W5,
+                W4,
+                W3,
+                W2,
+                W1,
+            ) as ComponentGroup>::read_from_sorted_pointers(&ptrs);
+            assert_eq!(read.0, W16(16));
+            assert_eq!(read.7, W9(9));
+            assert_eq!(read.15, W1(1));
+        }
+    }
+
     #[test]
     fn test_component_group_sorted_pointers_as_ref_tuples() {
         unsafe {
@@ -276,6 +363,19 @@ mod test {
         }
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_component_group_aliasing_slices() {
+        unsafe {
+            let mut slice_a = [A::default(), A::default()];
+            let mut pointers = [core::ptr::null_mut::<u8>(); MAX_COMPONENTS_PER_ENTITY];
+            pointers[0] = slice_a.as_mut_ptr() as *mut u8;
+            pointers[1] = slice_a.as_mut_ptr() as *mut u8;
+            let _ = <(A, B) as ComponentGroup>::slice_unchecked_mut(&pointers, slice_a.len());
+        }
+    }
+
     #[test]
     fn test_component_group_slices() {
         unsafe {
@@ -354,44 +454,44 @@ mod test {
     }
 }
 
-// impl_component_tuple!(
-//     16,
-//     (T1, 0),
-//     (T2, 1),
-//     (T3, 2),
-//     (T4, 3),
-//     (T5, 4),
-//     (T6, 5),
-//     (T7, 6),
-//     (T8, 7),
-//     (T9, 8),
-//     (T10, 9),
-//     (T11, 10),
-//     (T12, 11),
-//     (T13, 12),
-//     (T14, 13),
-//     (T15, 14),
-//     (T16, 15)
-// );
-//
-// impl_component_tuple!(
-//     15,
-//     (T1, 0),
-//     (T2, 1),
-//     (T3, 2),
-//     (T4, 3),
-//     (T5, 4),
-//     (T6, 5),
-//     (T7, 6),
-//     (T8, 7),
-//     (T9, 8),
-//     (T10, 9),
-//     (T11, 10),
-//     (T12, 11),
-//     (T13, 12),
-//     (T14, 13),
-//     (T15, 14)
-// );
+impl_component_tuple!(
+    16,
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10),
+    (T12, 11),
+    (T13, 12),
+    (T14, 13),
+    (T15, 14),
+    (T16, 15)
+);
+
+impl_component_tuple!(
+    15,
+    (T1, 0),
+    (T2, 1),
+    (T3, 2),
+    (T4, 3),
+    (T5, 4),
+    (T6, 5),
+    (T7, 6),
+    (T8, 7),
+    (T9, 8),
+    (T10, 9),
+    (T11, 10),
+    (T12, 11),
+    (T13, 12),
+    (T14, 13),
+    (T15, 14)
+);
 
 impl_component_tuple!(
     14,
@@ -563,11 +663,14 @@ mod tests {
     fn test_component_group_len() {
         fn test_group_len<G: ComponentGroup>(expected_len: usize) {
             assert_eq!(G::DESCRIPTOR.archetype().len() as usize, expected_len);
+            assert_eq!(G::LEN, expected_len);
         }
 
         test_group_len::<Position>(1);
         test_group_len::<(Position, Rotation)>(2);
         test_group_len::<(Position, Rotation, Velocity)>(3);
+        let array = [0u8; <(Rotation, Position) as ComponentGroup>::LEN];
+        assert_eq!(array.len(), 2);
     }
 
     #[test]
@@ -576,7 +679,19 @@ mod tests {
         extern crate std;
 
         assert!(<Position as ComponentGroup>::DESCRIPTOR.is_valid());
-        assert!(!<(Position, Position) as ComponentGroup>::DESCRIPTOR.is_valid());
         assert!(<(Position, Rotation) as ComponentGroup>::DESCRIPTOR.is_valid());
     }
+
+    #[test]
+    fn test_component_group_descriptors() {
+        let descriptors = <(Velocity, Position, Rotation) as ComponentGroup>::descriptors();
+        assert_eq!(descriptors.len(), 3);
+        assert!(descriptors
+            .windows(2)
+            .all(|pair| pair[0].component_type_id < pair[1].component_type_id));
+        assert!(descriptors
+            .iter()
+            .any(|descriptor| descriptor.component_type_id == Rotation::ID));
+        assert_eq!(<Position as ComponentGroup>::descriptors().len(), 1);
+    }
 }
@@ -53,10 +53,14 @@ impl ComponentGroupDescriptor {
 }
 
 impl ComponentGroupDescriptor {
+    /// Creates a new component group descriptor from the given component descriptors.
+    /// Intended to be evaluated in const context, rejecting invalid groups at compile time.
+    /// # Panics
+    /// - If no component descriptors are given.
+    /// - If more than [`MAX_COMPONENTS_PER_ENTITY`] component descriptors are given.
+    /// - If the same component type is present more than once.
     pub const fn new<const N: usize>(descriptors: &[ComponentDescriptor; N]) -> Self {
-        if !Self::validate_component_descriptors(descriptors) {
-            return Self::INVALID;
-        }
+        Self::validate_component_descriptors(descriptors);
 
         let sorted_descriptors = Self::compute_sorted_descriptors(descriptors);
 
@@ -79,12 +83,16 @@ impl ComponentGroupDescriptor {
         value
     }
 
+    /// Panics if the given descriptors do not form a valid component group.
     const fn validate_component_descriptors<const N: usize>(
         descriptors: &[ComponentDescriptor; N],
-    ) -> bool {
+    ) {
         // Length may not be zero or larger than max components.
-        if descriptors.is_empty() || descriptors.len() > MAX_COMPONENTS_PER_ENTITY {
-            return false;
+        if descriptors.is_empty() {
+            panic!("A component group must contain at least one component.");
+        }
+        if descriptors.len() > MAX_COMPONENTS_PER_ENTITY {
+            panic!("A component group must not contain more than MAX_COMPONENTS_PER_ENTITY components.");
         }
         // Duplicates MUST not exist.
         let mut idx = 0;
@@ -94,13 +102,12 @@ impl ComponentGroupDescriptor {
                 if descriptors[idx].component_type_id().into_u16()
                     == descriptors[cdx].component_type_id().into_u16()
                 {
-                    return false;
+                    panic!("A component group must not contain duplicate component types.");
                 }
                 cdx += 1;
             }
             idx += 1;
         }
-        true
     }
 
     /// Computes the sorted version of a given array of descriptors.
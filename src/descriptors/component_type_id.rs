@@ -13,7 +13,7 @@ impl From<u16> for ComponentTypeId {
 
 impl From<ComponentTypeId> for ArchetypeId {
     fn from(value: ComponentTypeId) -> Self {
-        ArchetypeId::from_u32(value.0 as u32)
+        ArchetypeId::from_parts(1, value.0 as u32)
     }
 }
 
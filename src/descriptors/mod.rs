@@ -5,6 +5,7 @@ pub mod component_descriptor;
 pub mod component_group;
 pub mod component_group_descriptor;
 pub mod component_type_id;
+pub mod stable_archetype_id;
 
 pub use archetype_id::*;
 pub use component::Component;
@@ -12,3 +13,4 @@ pub use component_descriptor::ComponentDescriptor;
 pub use component_group::ComponentGroup;
 pub use component_group_descriptor::ComponentGroupDescriptor;
 pub use component_type_id::*;
+pub use stable_archetype_id::*;
@@ -0,0 +1,23 @@
+/// Identifies an archetype within a registry for as long as the registry exists.
+/// Assigned in increasing order when an archetype is created and never reused,
+/// unlike the index of an archetype, which may change when archetypes are reorganized.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StableArchetypeId(u32);
+
+impl StableArchetypeId {
+    /// The invalid stable archetype id, used by archetypes which are not part of a registry.
+    pub const INVALID: StableArchetypeId = StableArchetypeId::from_u32(u32::MAX);
+    /// Returns true if the stable archetype id is valid.
+    pub const fn is_valid(&self) -> bool {
+        self.0 != Self::INVALID.0
+    }
+    /// Construct a stable archetype id from a u32.
+    pub const fn from_u32(v: u32) -> Self {
+        StableArchetypeId(v)
+    }
+    /// Construct a u32 from a stable archetype id.
+    pub const fn into_u32(self) -> u32 {
+        self.0
+    }
+}
@@ -80,6 +80,23 @@ impl Entity {
     pub const fn raw(&self) -> u32 {
         self.handle
     }
+
+    /// Returns a stable 64-bit representation of the entity.
+    /// The lower 32 bits contain the index, the upper 32 bits contain the version.
+    pub const fn to_bits(&self) -> u64 {
+        ((self.version() as u64) << 32) | self.index() as u64
+    }
+
+    /// Constructs an entity from it's 64-bit representation as returned by [`Entity::to_bits`].
+    /// Returns [`Entity::INVALID`] if the bits do not represent a valid entity handle.
+    pub const fn from_bits(bits: u64) -> Entity {
+        let index = bits as u32;
+        let version = bits >> 32;
+        if index > MAX_ENTITY_HANDLE_VALUE || version > MAX_ENTITY_VERSION_VALUE as u64 {
+            return Self::INVALID;
+        }
+        unsafe { Entity::new_unchecked(index, version as u8) }
+    }
 }
 
 #[test]
@@ -100,3 +117,25 @@ fn test_entity_handles() {
 
     assert_eq!(Entity::invalid().raw(), u32::MAX);
 }
+
+#[test]
+fn test_entity_bits() {
+    for (index, version) in [
+        (0, 0),
+        (1, 7),
+        (8_000_000, 255),
+        (MAX_ENTITY_HANDLE_VALUE, 0),
+    ] {
+        let entity = unsafe { Entity::new_unchecked(index, version) };
+        let bits = entity.to_bits();
+        assert_eq!(bits as u32, index);
+        assert_eq!((bits >> 32) as u8, version);
+        assert_eq!(Entity::from_bits(bits), entity);
+    }
+    assert_eq!(
+        Entity::from_bits(Entity::INVALID.to_bits()),
+        Entity::INVALID
+    );
+    assert_eq!(Entity::from_bits(u64::MAX), Entity::INVALID);
+    assert_eq!(Entity::from_bits(1 << 40), Entity::INVALID);
+}
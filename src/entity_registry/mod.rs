@@ -15,6 +15,9 @@ use alloc::vec::*;
 pub struct EntityRegistry {
     entities: Vec<EntityEntry>,
     next_free_slot: u32,
+    /// Versions of slots removed by [`Self::shrink_to_fit`], indexed by slot index.
+    /// Recreated slots continue from these, so stale handles to removed slots stay invalid.
+    retired_versions: Vec<u8>,
 }
 
 impl Default for EntityRegistry {
@@ -22,6 +25,7 @@ impl Default for EntityRegistry {
         Self {
             entities: Vec::new(),
             next_free_slot: IndexInArchetype::INVALID_VALUE,
+            retired_versions: Vec::new(),
         }
     }
 }
@@ -39,10 +43,13 @@ impl EntityRegistry {
         }
         let (entity, entry) = if self.next_free_slot == IndexInArchetype::INVALID_VALUE {
             // Linked list of free slots is empty, so we need to allocate a new entity.
-            self.entities.push(EntityEntry::default());
-            let idx = self.entities.len() - 1;
+            let idx = self.entities.len();
+            let version = self.retired_versions.get(idx).copied().unwrap_or(0);
+            let mut entry = EntityEntry::default();
+            entry.set_version(version);
+            self.entities.push(entry);
             (
-                unsafe { Entity::new_unchecked(idx as u32, 0) },
+                unsafe { Entity::new_unchecked(idx as u32, version) },
                 self.entities.last_mut().unwrap(),
             )
         } else {
@@ -98,6 +105,139 @@ impl EntityRegistry {
     pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
         EntityIter::new(&self.entities)
     }
+
+    /// Reserves slots for at least `additional` more entities than currently registered.
+    /// Never reserves beyond [`Self::MAX_ENTITY_COUNT`] slots in total.
+    pub fn reserve(&mut self, additional: usize) {
+        let additional = additional.min(Self::MAX_ENTITY_COUNT - self.entities.len());
+        self.entities.reserve(additional);
+    }
+
+    /// Returns the amount of slots which can be held without reallocating.
+    pub fn slot_capacity(&self) -> usize {
+        self.entities.capacity()
+    }
+
+    /// Removes all entities, keeping the allocated slots.
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.next_free_slot = IndexInArchetype::INVALID_VALUE;
+    }
+
+    /// Replaces all entities by the entities of [`source`], reusing the allocated slots.
+    pub fn clone_from(&mut self, source: &EntityRegistry) {
+        self.entities.clone_from(&source.entities);
+        self.next_free_slot = source.next_free_slot;
+        self.retired_versions.clone_from(&source.retired_versions);
+    }
+
+    /// Returns the amount of slots, both used by valid entities and free.
+    pub fn slot_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns the version stored in the slot with the given index, whether the slot is used or free.
+    /// Returns None if the index is out of range, including slots removed by [`Self::shrink_to_fit`].
+    pub fn slot_version(&self, index: u32) -> Option<u8> {
+        self.entities
+            .get(index as usize)
+            .map(|entry| entry.version())
+    }
+
+    /// Returns the lowest version which no handle to the slot with the given index has used yet.
+    fn unused_version(&self, index: usize) -> u8 {
+        match self.entities.get(index) {
+            // Destroying an entity already increments the version of its slot.
+            Some(entry) if entry.is_valid() => entry.version().wrapping_add(1),
+            Some(entry) => entry.version(),
+            None => self.retired_versions.get(index).copied().unwrap_or(0),
+        }
+    }
+
+    /// Increases the version of every slot past all versions used by the handles of [`previous`].
+    /// Afterwards no handle of [`previous`] is valid, neither for existing nor for recreated slots.
+    /// Entities keep their index, handles of existing entities must be updated using [`Self::slot_version`].
+    pub fn offset_versions_past(&mut self, previous: &EntityRegistry) {
+        for (index, entry) in self.entities.iter_mut().enumerate() {
+            entry.set_version(entry.version().wrapping_add(previous.unused_version(index)));
+        }
+        let slot_count = previous.entities.len().max(previous.retired_versions.len());
+        if self.retired_versions.len() < slot_count {
+            self.retired_versions.resize(slot_count, 0);
+        }
+        for index in self.entities.len()..self.retired_versions.len() {
+            self.retired_versions[index] =
+                self.retired_versions[index].wrapping_add(previous.unused_version(index));
+        }
+    }
+
+    /// Returns the amount of slots in the list of free slots.
+    /// Stops counting once every slot has been visited, in case the list contains a cycle.
+    pub fn free_slot_count(&self) -> usize {
+        let mut count = 0;
+        let mut slot = self.next_free_slot;
+        while slot != IndexInArchetype::INVALID_VALUE && count < self.entities.len() {
+            slot = self.entities[slot as usize].index_in_archetype().value();
+            count += 1;
+        }
+        count
+    }
+
+    /// Panics if the list of free slots is inconsistent with the entries.
+    /// Every invalid entry must be in the list of free slots exactly once.
+    pub fn check_invariants(&self) {
+        let free_count = self.entities.iter().filter(|e| !e.is_valid()).count();
+        let mut visited = 0;
+        let mut slot = self.next_free_slot;
+        while slot != IndexInArchetype::INVALID_VALUE {
+            assert!(
+                visited < free_count,
+                "The list of free slots contains a cycle or a valid entry."
+            );
+            let entry = &self.entities[slot as usize];
+            assert!(
+                !entry.is_valid(),
+                "Free slot {} contains a valid entity.",
+                slot
+            );
+            slot = entry.index_in_archetype().value();
+            visited += 1;
+        }
+        assert_eq!(
+            visited, free_count,
+            "Not every invalid entry is in the list of free slots."
+        );
+    }
+
+    /// Removes all unused slots past the highest valid entity and releases unused memory.
+    /// Valid entities keep their handles, only trailing slots are removed.
+    /// The versions of removed slots are kept, so stale handles to them stay invalid once the slots are recreated.
+    pub fn shrink_to_fit(&mut self) {
+        let len = self
+            .entities
+            .iter()
+            .rposition(|e| e.is_valid())
+            .map_or(0, |i| i + 1);
+        if len < self.entities.len() {
+            if self.retired_versions.len() < self.entities.len() {
+                self.retired_versions.resize(self.entities.len(), 0);
+            }
+            for (index, entry) in self.entities.iter().enumerate().skip(len) {
+                self.retired_versions[index] = entry.version();
+            }
+            self.entities.truncate(len);
+            // Rebuild the list of free slots, as it may point to removed slots.
+            self.next_free_slot = IndexInArchetype::INVALID_VALUE;
+            for (index, entry) in self.entities.iter_mut().enumerate().rev() {
+                if entry.is_valid() {
+                    continue;
+                }
+                unsafe { entry.invalidate(self.next_free_slot) };
+                self.next_free_slot = index as u32;
+            }
+        }
+        self.entities.shrink_to_fit();
+    }
 }
 
 #[cfg(test)]
@@ -205,4 +345,118 @@ mod tests {
             let _ = entities.contains(&entity);
         }
     }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        const COUNT: u32 = 1_000_000;
+        let mut registry = EntityRegistry::default();
+        let index_in_archetype = IndexInArchetype::new(2).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+
+        let entities = (0..COUNT)
+            .filter_map(|_| registry.create_entity(index_in_archetype, archetype_index))
+            .collect::<Vec<_>>();
+        assert_eq!(entities.len(), COUNT as usize);
+        // Destroy the top half by index and some entities below.
+        for entity in &entities[COUNT as usize / 2..] {
+            assert!(registry.destroy_entity(*entity));
+        }
+        for entity in entities[..COUNT as usize / 2].iter().step_by(3) {
+            assert!(registry.destroy_entity(*entity));
+        }
+        let capacity = registry.entities.capacity();
+
+        registry.shrink_to_fit();
+        verify_linked_list(&registry);
+        assert_eq!(registry.entities.len(), COUNT as usize / 2);
+        assert!(registry.entities.capacity() < capacity);
+        for (index, entity) in entities.iter().enumerate() {
+            let valid = index < COUNT as usize / 2 && index % 3 != 0;
+            assert_eq!(registry.entity_entry(*entity).is_some(), valid);
+        }
+
+        // Free slots are reused before new slots are allocated.
+        let entity = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        assert_eq!(entity.index(), 0);
+        assert_eq!(entity.version(), 1);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_keeps_versions() {
+        let mut registry = EntityRegistry::default();
+        let index_in_archetype = IndexInArchetype::new(0).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+
+        let first = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        let stale = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        assert!(registry.destroy_entity(stale));
+        registry.shrink_to_fit();
+        assert_eq!(registry.slot_count(), 1);
+        assert!(registry.entity_entry(first).is_some());
+
+        // The removed slot is recreated with the version it had before shrinking.
+        let recreated = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        assert_eq!(recreated.index(), stale.index());
+        assert_eq!(recreated.version(), 1);
+        assert!(registry.entity_entry(stale).is_none());
+        assert!(!registry.destroy_entity(stale));
+
+        // Shrinking again keeps the version of the recreated slot.
+        assert!(registry.destroy_entity(recreated));
+        registry.shrink_to_fit();
+        let recreated_again = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        assert_eq!(recreated_again.version(), 2);
+        assert!(registry.entity_entry(stale).is_none());
+        assert!(registry.entity_entry(recreated).is_none());
+        registry.check_invariants();
+    }
+
+    #[test]
+    fn test_offset_versions_past() {
+        let index_in_archetype = IndexInArchetype::new(0).unwrap();
+        let archetype_index = ArchetypeIndex::new(1).unwrap();
+        let mut previous = EntityRegistry::default();
+        let live = previous
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        let destroyed = previous
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        let retired = previous
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        assert!(previous.destroy_entity(destroyed));
+        assert!(previous.destroy_entity(retired));
+        previous.shrink_to_fit();
+        assert_eq!(previous.slot_count(), 1);
+
+        let mut registry = EntityRegistry::default();
+        let first = registry
+            .create_entity(index_in_archetype, archetype_index)
+            .unwrap();
+        registry.offset_versions_past(&previous);
+        assert_eq!(registry.slot_version(first.index()), Some(1));
+        assert!(registry.entity_entry(live).is_none());
+        assert!(registry.entity_entry(first).is_none());
+
+        // Slots which are created afterwards skip the versions used in previous as well.
+        for stale in [destroyed, retired] {
+            let entity = registry
+                .create_entity(index_in_archetype, archetype_index)
+                .unwrap();
+            assert_eq!(entity.index(), stale.index());
+            assert_ne!(entity.version(), stale.version());
+            assert!(registry.entity_entry(stale).is_none());
+        }
+    }
 }
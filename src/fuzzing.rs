@@ -0,0 +1,299 @@
+//! Harness for fuzzing sequences of structural changes on a [`Registry`].
+//! Enabled by the `fuzzing` feature and used by the fuzz target in the `fuzz` directory.
+//! The tests of this module run the harness on random input, which is also useful under Miri.
+
+use crate::*;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Component without any heap allocations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Small(pub u32);
+
+impl Component for Small {
+    const NAME: &'static str = "shard_ecs::fuzzing::Small";
+}
+
+/// Component owning heap memory, so double drops and leaks are detectable by sanitizers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heap(pub Vec<u8>);
+
+impl Component for Heap {
+    const NAME: &'static str = "shard_ecs::fuzzing::Heap";
+}
+
+/// Component with a larger alignment than its members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(align(32))]
+pub struct Aligned(pub u64);
+
+impl Component for Aligned {
+    const NAME: &'static str = "shard_ecs::fuzzing::Aligned";
+}
+
+/// A single operation applied to the registry by [`run`].
+/// Entities are referred to by their index in the list of all entities created so far, modulo its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// Creates an entity with the components selected by the lowest three bits of the mask.
+    /// Creates an entity with just [`Small`] if none are selected.
+    Create { mask: u8, value: u8 },
+    /// Destroys the entity.
+    Destroy { entity: u8 },
+    /// Adds the component with the given index to the entity.
+    AddComponent {
+        entity: u8,
+        component: u8,
+        value: u8,
+    },
+    /// Removes the component with the given index from the entity.
+    RemoveComponent { entity: u8, component: u8 },
+}
+
+impl Op {
+    /// Decodes a sequence of operations from raw fuzzer input, using three bytes per operation.
+    pub fn decode(data: &[u8]) -> impl Iterator<Item = Op> + '_ {
+        data.chunks_exact(3).map(|bytes| match bytes[0] % 4 {
+            0 => Op::Create {
+                mask: bytes[1],
+                value: bytes[2],
+            },
+            1 => Op::Destroy { entity: bytes[1] },
+            2 => Op::AddComponent {
+                entity: bytes[1],
+                component: bytes[2] % 3,
+                value: bytes[2],
+            },
+            _ => Op::RemoveComponent {
+                entity: bytes[1],
+                component: bytes[2] % 3,
+            },
+        })
+    }
+}
+
+/// The components an entity is expected to have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expected {
+    small: Option<Small>,
+    heap: Option<Heap>,
+    aligned: Option<Aligned>,
+}
+
+impl Expected {
+    fn new(mask: u8, value: u8) -> Self {
+        let mask = if mask & 0b111 == 0 { 0b001 } else { mask };
+        Self {
+            small: (mask & 0b001 != 0).then_some(Small(value as u32)),
+            heap: (mask & 0b010 != 0).then(|| Heap(alloc::vec![value; value as usize % 17])),
+            aligned: (mask & 0b100 != 0).then_some(Aligned(value as u64)),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.small.is_some() as usize
+            + self.heap.is_some() as usize
+            + self.aligned.is_some() as usize
+    }
+
+    fn create(&self, registry: &mut Registry) -> Result<Entity, ()> {
+        let (small, heap, aligned) = (self.small.clone(), self.heap.clone(), self.aligned.clone());
+        match (small, heap, aligned) {
+            (Some(s), None, None) => registry.create_entity(s).map_err(|_| ()),
+            (None, Some(h), None) => registry.create_entity(h).map_err(|_| ()),
+            (None, None, Some(a)) => registry.create_entity(a).map_err(|_| ()),
+            (Some(s), Some(h), None) => registry.create_entity((s, h)).map_err(|_| ()),
+            (Some(s), None, Some(a)) => registry.create_entity((a, s)).map_err(|_| ()),
+            (None, Some(h), Some(a)) => registry.create_entity((h, a)).map_err(|_| ()),
+            (Some(s), Some(h), Some(a)) => registry.create_entity((h, s, a)).map_err(|_| ()),
+            (None, None, None) => unreachable!(),
+        }
+    }
+
+    fn verify(&self, registry: &Registry, entity: Entity) {
+        assert_eq!(registry.get_component::<Small>(entity), self.small.as_ref());
+        assert_eq!(registry.get_component::<Heap>(entity), self.heap.as_ref());
+        assert_eq!(
+            registry.get_component::<Aligned>(entity),
+            self.aligned.as_ref()
+        );
+        if let Some(aligned) = registry.get_component::<Aligned>(entity) {
+            assert_eq!(aligned as *const Aligned as usize % 32, 0);
+        }
+    }
+}
+
+/// Applies the operations to a new registry.
+/// After every operation the invariants of the registry are checked,
+/// and the components of all entities are compared against a model of the registry.
+/// Panics if the registry does not behave like the model.
+pub fn run(ops: impl IntoIterator<Item = Op>) {
+    let mut registry = Registry::default();
+    let mut entities: Vec<Entity> = Vec::new();
+    let mut model: BTreeMap<u64, Expected> = BTreeMap::new();
+
+    for op in ops {
+        let pick = |index: u8| -> Option<Entity> {
+            if entities.is_empty() {
+                return None;
+            }
+            Some(entities[index as usize % entities.len()])
+        };
+        match op {
+            Op::Create { mask, value } => {
+                let expected = Expected::new(mask, value);
+                let entity = expected.create(&mut registry).unwrap();
+                assert!(model.insert(entity.to_bits(), expected).is_none());
+                entities.push(entity);
+            }
+            Op::Destroy { entity } => {
+                let entity = match pick(entity) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let was_alive = model.remove(&entity.to_bits()).is_some();
+                assert_eq!(registry.destroy_entity(entity), was_alive);
+            }
+            Op::AddComponent {
+                entity,
+                component,
+                value,
+            } => {
+                let entity = match pick(entity) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let expected = model.get_mut(&entity.to_bits());
+                let added = match component {
+                    0 => registry.add_component(entity, Small(value as u32)).is_ok(),
+                    1 => registry
+                        .add_component(entity, Heap(alloc::vec![value; 3]))
+                        .is_ok(),
+                    _ => registry
+                        .add_component(entity, Aligned(value as u64))
+                        .is_ok(),
+                };
+                match expected {
+                    // Adding a component that is already present fails and keeps the old value.
+                    Some(expected) => {
+                        let present = match component {
+                            0 => expected.small.is_some(),
+                            1 => expected.heap.is_some(),
+                            _ => expected.aligned.is_some(),
+                        };
+                        assert_eq!(added, !present);
+                        if !present {
+                            match component {
+                                0 => expected.small = Some(Small(value as u32)),
+                                1 => expected.heap = Some(Heap(alloc::vec![value; 3])),
+                                _ => expected.aligned = Some(Aligned(value as u64)),
+                            }
+                        }
+                    }
+                    None => assert!(!added),
+                }
+            }
+            Op::RemoveComponent { entity, component } => {
+                let entity = match pick(entity) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let expected = model.get_mut(&entity.to_bits());
+                match expected {
+                    Some(expected) if expected.len() > 1 => match component {
+                        0 => assert_eq!(
+                            registry.remove_component::<Small>(entity).ok(),
+                            expected.small.take()
+                        ),
+                        1 => assert_eq!(
+                            registry.remove_component::<Heap>(entity).ok(),
+                            expected.heap.take()
+                        ),
+                        _ => assert_eq!(
+                            registry.remove_component::<Aligned>(entity).ok(),
+                            expected.aligned.take()
+                        ),
+                    },
+                    // Removing the last component of an entity removes the entity.
+                    Some(expected) => {
+                        let removed = match component {
+                            0 => {
+                                let removed = registry.remove_component::<Small>(entity).ok();
+                                assert_eq!(removed, expected.small.take());
+                                removed.is_some()
+                            }
+                            1 => {
+                                let removed = registry.remove_component::<Heap>(entity).ok();
+                                assert_eq!(removed, expected.heap.take());
+                                removed.is_some()
+                            }
+                            _ => {
+                                let removed = registry.remove_component::<Aligned>(entity).ok();
+                                assert_eq!(removed, expected.aligned.take());
+                                removed.is_some()
+                            }
+                        };
+                        if removed {
+                            model.remove(&entity.to_bits());
+                        }
+                    }
+                    None => {
+                        let removed = match component {
+                            0 => registry.remove_component::<Small>(entity).is_ok(),
+                            1 => registry.remove_component::<Heap>(entity).is_ok(),
+                            _ => registry.remove_component::<Aligned>(entity).is_ok(),
+                        };
+                        assert!(!removed);
+                    }
+                }
+            }
+        }
+
+        registry.check_invariants();
+        assert_eq!(registry.iter_entities().count(), model.len());
+        for entity in &entities {
+            match model.get(&entity.to_bits()) {
+                Some(expected) => expected.verify(&registry, *entity),
+                None => assert!(registry.entity_location(*entity).is_none()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_fuzzing_harness_random_input() {
+        let mut rng = StdRng::seed_from_u64(0x5EED);
+        let iterations = if cfg!(miri) { 4 } else { 64 };
+        for _ in 0..iterations {
+            let len = rng.gen_range(0..if cfg!(miri) { 96 } else { 3 * 512 });
+            let data = (0..len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            run(Op::decode(&data));
+        }
+    }
+
+    #[test]
+    fn test_fuzzing_harness_decode() {
+        let ops = Op::decode(&[0, 7, 1, 5, 0, 0, 2, 0, 4, 3, 0, 1, 9]).collect::<Vec<_>>();
+        assert_eq!(
+            ops,
+            [
+                Op::Create { mask: 7, value: 1 },
+                Op::Destroy { entity: 0 },
+                Op::AddComponent {
+                    entity: 0,
+                    component: 1,
+                    value: 4
+                },
+                Op::RemoveComponent {
+                    entity: 0,
+                    component: 1
+                },
+            ]
+        );
+    }
+}
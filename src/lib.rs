@@ -8,13 +8,25 @@ pub mod constants;
 pub mod descriptors;
 pub mod entity_registry;
 pub mod fnv1a;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
 pub mod registry;
+pub mod tick;
 
-pub use archetype::Archetype;
+#[cfg(all(feature = "mmap", unix))]
+pub use archetype::MmapBackend;
+#[cfg(feature = "pool")]
+pub use archetype::StoragePool;
+pub use archetype::{AllocError, Archetype, ColumnBackend, HeapBackend, RawColumn};
 pub use constants::*;
 pub use descriptors::*;
 pub use entity_registry::*;
-pub use registry::Registry;
+pub use registry::{
+    AccessError, AddComponentError, ApplyError, ArchetypeLayout, ComponentConflict,
+    ComponentLayout, DynBundle, EntityAudit, EntityRef, EntityRefMut, QueryError, Registry,
+    RemoveComponentError, ReserveError, ScopedCommands,
+};
+pub use tick::Tick;
 
 #[cfg(test)]
 mod test_components;
@@ -0,0 +1,22 @@
+use core::fmt;
+
+/// Error returned by [`crate::Registry::try_get_component`] if the component cannot be accessed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    /// The entity handle is [`crate::Entity::INVALID`].
+    NullEntity,
+    /// The entity was destroyed or never existed in the registry.
+    DeadEntity,
+    /// The entity does not have the component.
+    ComponentNotPresent,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::NullEntity => write!(f, "the entity handle is null"),
+            AccessError::DeadEntity => write!(f, "the entity was destroyed or never existed"),
+            AccessError::ComponentNotPresent => write!(f, "the entity does not have the component"),
+        }
+    }
+}
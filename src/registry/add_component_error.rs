@@ -0,0 +1,41 @@
+use core::fmt;
+
+/// Error returned by [`crate::Registry::add_component`] if the component could not be added.
+/// Contains the component which was not added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AddComponentError<C> {
+    /// The entity handle is invalid.
+    InvalidEntity(C),
+    /// The entity already has the component, which is left unchanged.
+    AlreadyPresent(C),
+    /// The destination archetype could not be created or is full, as an internal limit is exceeded.
+    LimitExceeded(C),
+}
+
+impl<C> AddComponentError<C> {
+    /// Returns the component which was not added.
+    pub fn into_component(self) -> C {
+        match self {
+            AddComponentError::InvalidEntity(component)
+            | AddComponentError::AlreadyPresent(component)
+            | AddComponentError::LimitExceeded(component) => component,
+        }
+    }
+}
+
+impl<C> fmt::Display for AddComponentError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddComponentError::InvalidEntity(_) => write!(f, "the entity is invalid"),
+            AddComponentError::AlreadyPresent(_) => {
+                write!(f, "the entity already has the component")
+            }
+            AddComponentError::LimitExceeded(_) => {
+                write!(
+                    f,
+                    "the destination archetype could not be created or is full"
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use core::fmt;
+
+/// Error returned by [`crate::Registry::apply_raw_component`] if the bytes could not be applied.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    /// The entity handle is invalid.
+    InvalidEntity,
+    /// The entity does not have a component with the given type id.
+    MissingComponent,
+    /// The amount of bytes does not match the size of the component.
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyError::InvalidEntity => write!(f, "the entity is invalid"),
+            ApplyError::MissingComponent => write!(f, "the entity does not have the component"),
+            ApplyError::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected {} bytes for the component, got {}",
+                expected, actual
+            ),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::ComponentTypeId;
+use core::fmt;
+
+/// Error returned by [`crate::Registry::register_component`] if a different type
+/// has already been registered using the same [`ComponentTypeId`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ComponentConflict {
+    /// The component type id both types share.
+    pub id: ComponentTypeId,
+    /// Name of the type which registered the id first.
+    pub registered: &'static str,
+    /// Name of the type which was rejected.
+    pub conflicting: &'static str,
+}
+
+impl fmt::Display for ComponentConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "component type id {:?} of {} is already registered by {}",
+            self.id, self.conflicting, self.registered
+        )
+    }
+}
@@ -0,0 +1,75 @@
+use crate::archetype::Archetype;
+use crate::descriptors::component_group::ComponentGroup;
+use crate::entity_registry::EntityRegistry;
+use crate::Entity;
+use core::marker::PhantomData;
+
+/// Moves the entities out of an archetype, see [`crate::Registry::drain_archetype`].
+/// Entities which are not yielded are dropped when the iterator is dropped.
+pub(crate) struct DrainArchetype<'r, G: ComponentGroup> {
+    archetype: Option<&'r mut Archetype>,
+    entities: &'r mut EntityRegistry,
+    _group: PhantomData<G>,
+}
+
+impl<'r, G: ComponentGroup> DrainArchetype<'r, G> {
+    /// The archetype must store exactly the components in [`G`].
+    pub(crate) fn new(
+        archetype: Option<&'r mut Archetype>,
+        entities: &'r mut EntityRegistry,
+    ) -> Self {
+        if let Some(archetype) = &archetype {
+            debug_assert_eq!(
+                archetype.descriptor().archetype_id(),
+                G::DESCRIPTOR.archetype().archetype_id()
+            );
+        }
+        Self {
+            archetype,
+            entities,
+            _group: PhantomData,
+        }
+    }
+}
+
+impl<'r, G: ComponentGroup> Iterator for DrainArchetype<'r, G> {
+    type Item = (Entity, G);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let archetype = self.archetype.as_mut()?;
+        if archetype.len() == 0 {
+            return None;
+        }
+        // Take from the end, so no other entities need to be moved.
+        let index = archetype.len() - 1;
+        let entity = archetype.entities()[index as usize];
+        let (components, _) = unsafe { archetype.swap_remove_unchecked::<G>(index) };
+        let _v = self.entities.destroy_entity(entity);
+        debug_assert!(_v);
+        Some((entity, components))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self
+            .archetype
+            .as_ref()
+            .map_or(0, |archetype| archetype.len() as usize);
+        (len, Some(len))
+    }
+}
+
+impl<'r, G: ComponentGroup> Drop for DrainArchetype<'r, G> {
+    fn drop(&mut self) {
+        let archetype = match self.archetype.as_mut() {
+            Some(v) => v,
+            None => return,
+        };
+        while archetype.len() > 0 {
+            let index = archetype.len() - 1;
+            let entity = archetype.entities()[index as usize];
+            unsafe { archetype.swap_drop_unchecked(index) };
+            let _v = self.entities.destroy_entity(entity);
+            debug_assert!(_v);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::descriptors::component_descriptor::ComponentDescriptor;
+
+/// A group of components whose types are only known at runtime.
+/// Used to spawn entities whose shape varies per entity, see [`crate::Registry::spawn_dynamic`].
+/// # Safety
+/// - [`DynBundle::descriptors`] must be sorted by component type id and may not contain duplicates.
+/// - Each descriptor must match the type of the component written for it, including its drop handler.
+/// - The registry takes ownership of the written components and drops them using the drop handlers.
+///   As the bundle is only borrowed, components which are not plain data must be cloned when written,
+///   so the bundle and the registry never drop the same instance.
+pub unsafe trait DynBundle {
+    /// Returns the descriptors of the components in the bundle, sorted by component type id.
+    fn descriptors(&self) -> &[ComponentDescriptor];
+
+    /// Writes the components of the bundle to the destinations.
+    /// Each destination corresponds to the descriptor at the same index.
+    /// # Safety
+    /// - Every destination is valid for writes of the corresponding component and properly aligned.
+    /// - The destinations contain no initialized components, they must not be read or dropped.
+    unsafe fn write_components(&self, destinations: &[*mut u8]);
+}
@@ -0,0 +1,10 @@
+/// Summary of the entity bookkeeping of a registry, as returned by [`crate::Registry::audit_entities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityAudit {
+    /// Amount of valid entities.
+    pub live: usize,
+    /// Amount of slots in the list of free slots.
+    pub free: usize,
+    /// Amount of slots in the entity registry, both used and free.
+    pub total_slots: usize,
+}
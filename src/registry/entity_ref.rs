@@ -0,0 +1,117 @@
+use crate::archetype::Archetype;
+use crate::descriptors::archetype_id::ArchetypeId;
+use crate::{Component, Entity, Tick};
+
+/// Shared access to the components of a single entity, see [`crate::Registry::entity_ref`].
+/// The location of the entity is resolved once, so repeated accesses do not look it up again.
+#[derive(Clone, Copy)]
+pub struct EntityRef<'r> {
+    entity: Entity,
+    archetype: &'r Archetype,
+    index: u32,
+}
+
+impl<'r> EntityRef<'r> {
+    /// The index must be a valid row of the archetype storing the entity.
+    pub(crate) fn new(entity: Entity, archetype: &'r Archetype, index: u32) -> Self {
+        debug_assert!(index < archetype.len());
+        Self {
+            entity,
+            archetype,
+            index,
+        }
+    }
+
+    /// Returns the entity this reference points to.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns the archetype id of the archetype storing the entity.
+    pub fn archetype_id(&self) -> ArchetypeId {
+        self.archetype.descriptor().archetype_id()
+    }
+
+    /// Returns the amount of components the entity has.
+    pub fn component_count(&self) -> u8 {
+        self.archetype.descriptor().len()
+    }
+
+    /// Returns true if the entity has the specified component.
+    pub fn contains<C: Component>(&self) -> bool {
+        self.archetype.descriptor().has_component::<C>()
+    }
+
+    /// Returns a reference to the specified component if the entity has it.
+    pub fn get<C: Component>(&self) -> Option<&'r C> {
+        if !self.contains::<C>() {
+            return None;
+        }
+        unsafe { Some(self.archetype.get_component_unchecked::<C>(self.index)) }
+    }
+}
+
+/// Mutable access to the components of a single entity, see [`crate::Registry::entity_ref_mut`].
+/// The location of the entity is resolved once, so repeated accesses do not look it up again.
+pub struct EntityRefMut<'r> {
+    entity: Entity,
+    archetype: &'r mut Archetype,
+    index: u32,
+    change_tick: &'r mut Tick,
+}
+
+impl<'r> EntityRefMut<'r> {
+    /// The index must be a valid row of the archetype storing the entity.
+    pub(crate) fn new(
+        entity: Entity,
+        archetype: &'r mut Archetype,
+        index: u32,
+        change_tick: &'r mut Tick,
+    ) -> Self {
+        debug_assert!(index < archetype.len());
+        Self {
+            entity,
+            archetype,
+            index,
+            change_tick,
+        }
+    }
+
+    /// Returns the entity this reference points to.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns the archetype id of the archetype storing the entity.
+    pub fn archetype_id(&self) -> ArchetypeId {
+        self.archetype.descriptor().archetype_id()
+    }
+
+    /// Returns the amount of components the entity has.
+    pub fn component_count(&self) -> u8 {
+        self.archetype.descriptor().len()
+    }
+
+    /// Returns true if the entity has the specified component.
+    pub fn contains<C: Component>(&self) -> bool {
+        self.archetype.descriptor().has_component::<C>()
+    }
+
+    /// Returns a reference to the specified component if the entity has it.
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        if !self.contains::<C>() {
+            return None;
+        }
+        unsafe { Some(self.archetype.get_component_unchecked::<C>(self.index)) }
+    }
+
+    /// Returns a mutable reference to the specified component if the entity has it.
+    /// Marks the archetype storing the entity as written to.
+    pub fn get_mut<C: Component>(&mut self) -> Option<&mut C> {
+        if !self.contains::<C>() {
+            return None;
+        }
+        self.archetype.mark_write(self.change_tick.increment());
+        unsafe { Some(self.archetype.get_component_unchecked_mut::<C>(self.index)) }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{ArchetypeId, ComponentTypeId};
+use alloc::vec::Vec;
+
+/// Describes the memory layout of a single component column in an archetype.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentLayout {
+    pub component_type_id: ComponentTypeId,
+    pub size: u16,
+    pub align: u16,
+    pub column_align: u16,
+    /// Amount of bytes allocated for the column.
+    pub allocated_bytes: usize,
+}
+
+/// Describes the memory layout of an archetype.
+/// Every component is stored in a separate column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchetypeLayout {
+    pub archetype_id: ArchetypeId,
+    pub entity_count: u32,
+    pub capacity: u32,
+    /// Sorted by component type id, in the order the columns are stored in.
+    pub components: Vec<ComponentLayout>,
+    /// Amount of bytes allocated for all columns, including the entity handles.
+    pub allocated_bytes: usize,
+}
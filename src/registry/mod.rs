@@ -1,6 +1,31 @@
 #[cfg(test)]
 mod tests;
 
+mod access_error;
+mod add_component_error;
+mod apply_error;
+mod component_conflict;
+mod drain_archetype;
+mod dyn_bundle;
+mod entity_audit;
+mod entity_ref;
+mod layout_report;
+mod query_error;
 mod registry;
+mod remove_component_error;
+mod reserve_error;
+mod scoped_commands;
 
+pub use access_error::*;
+pub use add_component_error::*;
+pub use apply_error::*;
+pub use component_conflict::*;
+pub use dyn_bundle::*;
+pub use entity_audit::*;
+pub use entity_ref::*;
+pub use layout_report::*;
+pub use query_error::*;
 pub use registry::*;
+pub use remove_component_error::*;
+pub use reserve_error::*;
+pub use scoped_commands::*;
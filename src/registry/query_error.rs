@@ -0,0 +1,13 @@
+use core::fmt;
+
+/// Error returned by [`crate::Registry::try_query`] if a query cannot be constructed.
+/// Has no variants, as every query which compiles can be constructed:
+/// component groups with duplicate or too many components are rejected at compile time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QueryError {}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
@@ -1,16 +1,35 @@
-use crate::ArchetypeIndex;
-use crate::archetype::Archetype;
+#[cfg(feature = "pool")]
+use crate::archetype::StoragePool;
+use crate::archetype::{Archetype, ColumnBackend, RawColumn};
 use crate::archetype_descriptor::ArchetypeDescriptor;
 use crate::archetype_registry::ArchetypeRegistry;
 use crate::descriptors::component_group::ComponentGroup;
 use crate::entity_registry::IndexInArchetype;
-use crate::{entity_registry::EntityRegistry, Component, Entity};
+use crate::registry::drain_archetype::DrainArchetype;
+use crate::registry::{
+    AccessError, AddComponentError, ApplyError, ArchetypeLayout, ComponentConflict,
+    ComponentLayout, DynBundle, EntityAudit, EntityRef, EntityRefMut, QueryError,
+    RemoveComponentError, ReserveError, ScopedCommands,
+};
+use crate::ArchetypeIndex;
+use crate::{
+    entity_registry::EntityRegistry, ArchetypeId, Component, ComponentTypeId, Entity,
+    StableArchetypeId, Tick, MAX_COMPONENTS_PER_ENTITY, MAX_ENTITIES_PER_ARCHETYPE,
+};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::{type_name, TypeId};
+use core::ops::Range;
 
 /// The primary construct in the *Shard* Entity Component System (ECS).
 #[derive(Default)]
 pub struct Registry {
-    entities: EntityRegistry,
-    archetypes: ArchetypeRegistry,
+    pub(super) entities: EntityRegistry,
+    pub(super) archetypes: ArchetypeRegistry,
+    registered_components: BTreeMap<ComponentTypeId, (TypeId, &'static str)>,
+    change_tick: Tick,
+    #[cfg(feature = "stats")]
+    transition_counts: BTreeMap<(ArchetypeId, ArchetypeId), u64>,
 }
 
 impl Registry {
@@ -19,6 +38,35 @@ impl Registry {
     /// Returns Err if the provided component group is invalid or an internal limit is exceeded.
     /// Panics in case of allocation failure.
     pub fn create_entity<'c, G: ComponentGroup>(&mut self, components: G) -> Result<Entity, G> {
+        self.create_entity_returning_location(components)
+            .map(|(entity, _, _)| entity)
+    }
+
+    /// Creates a new entity using the provided components, like [`Registry::create_entity`].
+    /// Panics if the provided component group is invalid or an internal limit is exceeded,
+    /// use [`Registry::create_entity`] to handle these cases instead.
+    /// Panics in case of allocation failure.
+    pub fn spawn<G: ComponentGroup>(&mut self, components: G) -> Entity {
+        match self.create_entity(components) {
+            Ok(entity) => entity,
+            Err(_) => panic!(
+                "Could not spawn an entity with components {}, the component group is invalid \
+                 or the maximum amount of archetypes or entities is exceeded.",
+                type_name::<G>()
+            ),
+        }
+    }
+
+    /// Creates a new entity using the provided components.
+    /// Returns Ok with the entity, the id of its archetype and its index within that archetype if successful,
+    /// or Err(components) if not.
+    /// The location is only valid until the next structural change to that archetype.
+    /// Returns Err if the provided component group is invalid or an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn create_entity_returning_location<G: ComponentGroup>(
+        &mut self,
+        components: G,
+    ) -> Result<(Entity, ArchetypeId, u32), G> {
         if !G::DESCRIPTOR.is_valid() {
             return Err(components);
         }
@@ -29,13 +77,211 @@ impl Registry {
             Some(v) => v,
             None => return Err(components),
         };
+        if archetype.is_at_entity_limit() {
+            return Err(components);
+        }
         let index_in_archetype = IndexInArchetype::new(archetype.len()).unwrap();
         let entity = match self.entities.create_entity(index_in_archetype, ArchetypeIndex::new(archetype_index).unwrap()) {
             Some(v) => v,
             None => return Err(components),
         };
         unsafe { archetype.push_entity_unchecked(entity, components) };
-        Ok(entity)
+        archetype.mark_structural_change(self.change_tick.increment());
+        Ok((
+            entity,
+            archetype.descriptor().archetype_id(),
+            index_in_archetype.value(),
+        ))
+    }
+
+    /// Creates a new entity using the provided components.
+    /// Returns Ok with the entity and a mutable reference to its component [`C`] if successful,
+    /// or Err(components) if not.
+    /// Fails to build if [`C`] is not part of [`G`], which `cargo check` does not detect as it is generic.
+    /// Returns Err if the provided component group is invalid or an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn spawn_mut<G: ComponentGroup, C: Component>(
+        &mut self,
+        components: G,
+    ) -> Result<(Entity, &mut C), G> {
+        const {
+            assert!(
+                G::DESCRIPTOR
+                    .archetype()
+                    .contains_subset(<C as ComponentGroup>::DESCRIPTOR.archetype()),
+                "The component must be part of the component group."
+            )
+        };
+        let (entity, _, index_in_archetype) = self.create_entity_returning_location(components)?;
+        let archetype_index = self
+            .entities
+            .entity_entry(entity)
+            .unwrap()
+            .archetype_index();
+        let archetype = &mut self.archetypes[archetype_index];
+        archetype.mark_write(self.change_tick.increment());
+        Ok((entity, unsafe {
+            archetype.get_component_unchecked_mut::<C>(index_in_archetype)
+        }))
+    }
+
+    /// Creates a new entity using the components of a bundle whose shape is only known at runtime.
+    /// Entities spawned this way share archetypes with entities created from component groups.
+    /// Returns None if the bundle's descriptors are invalid or an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn spawn_dynamic(&mut self, bundle: &dyn DynBundle) -> Option<Entity> {
+        let descriptor = ArchetypeDescriptor::from_sorted_components(bundle.descriptors())?;
+        if !self.entities.can_register_new_entity() {
+            return None;
+        }
+        let (archetype_index, archetype) = self.archetypes.find_or_create_archetype(&descriptor)?;
+        if archetype.is_at_entity_limit() {
+            return None;
+        }
+        let index_in_archetype = IndexInArchetype::new(archetype.len()).unwrap();
+        // Only register the entity once the components are written, in case the bundle panics.
+        let index = unsafe {
+            archetype.push_entity_with_unchecked(Entity::invalid(), |destinations| {
+                bundle.write_components(destinations)
+            })
+        };
+        let entity = self
+            .entities
+            .create_entity(
+                index_in_archetype,
+                ArchetypeIndex::new(archetype_index).unwrap(),
+            )
+            .unwrap();
+        archetype.entities_mut()[index as usize] = entity;
+        archetype.mark_structural_change(self.change_tick.increment());
+        Some(entity)
+    }
+
+    /// Creates an entity for every component group in the iterator, in consecutive rows of their archetype.
+    /// Returns the range of rows the entities were placed in, no other entities are placed in between.
+    /// The range is only valid until the next structural change to that archetype,
+    /// e.g. destroying an entity moves the last row of its archetype.
+    /// Component groups for which no entity could be created are dropped.
+    /// Panics in case of allocation failure.
+    pub fn spawn_contiguous<G, I>(&mut self, iter: I) -> Range<u32>
+    where
+        G: ComponentGroup,
+        I: IntoIterator<Item = G>,
+    {
+        let mut iter = iter.into_iter();
+        let additional = iter.size_hint().0.min(u32::MAX as usize) as u32;
+        self.spawn_rows::<G, _>(additional, |archetype| match iter.next() {
+            Some(components) => {
+                unsafe { archetype.push_entity_unchecked(Entity::INVALID, components) };
+                true
+            }
+            None => false,
+        })
+        .map_or(0..0, |(_, rows)| rows)
+    }
+
+    /// Creates `count` new entities, each using a clone of the provided components.
+    /// Capacity for all entities is reserved up front.
+    /// Returns the created entities, which may be fewer than `count` if an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn spawn_batch_same<G: ComponentGroup + Clone>(
+        &mut self,
+        template: G,
+        count: u32,
+    ) -> Vec<Entity> {
+        let mut remaining = count;
+        let spawned = self.spawn_rows::<G, _>(count, |archetype| {
+            if remaining == 0 {
+                return false;
+            }
+            remaining -= 1;
+            unsafe { archetype.push_entity_unchecked(Entity::INVALID, template.clone()) };
+            true
+        });
+        self.spawned_entities(spawned)
+    }
+
+    /// Creates `count` new entities using the components returned by [`f`] for each index in `0..count`.
+    /// Components are constructed and written in parallel, capacity for all entities is reserved up front.
+    /// Returns the created entities, which may be fewer than `count` if an internal limit is exceeded.
+    #[cfg(feature = "parallel")]
+    pub fn par_spawn_batch<G, F>(&mut self, count: u32, f: F) -> Vec<Entity>
+    where
+        G: ComponentGroup + Copy + Send,
+        F: Fn(usize) -> G + Sync,
+    {
+        let mut remaining = None;
+        let spawned = self.spawn_rows::<G, _>(count, |archetype| {
+            // Written once up front, G is Copy so rows left unused need not be dropped.
+            let remaining = remaining.get_or_insert_with(|| {
+                let first = archetype.len();
+                let count = core::cmp::min(count, archetype.capacity() - first);
+                // Rows past the end of the archetype are not observable,
+                // so a panic in f leaves the registry intact.
+                unsafe { archetype.par_write_components_unchecked(first, count, &f) };
+                count
+            });
+            if *remaining == 0 {
+                return false;
+            }
+            *remaining -= 1;
+            unsafe { archetype.push_uninitialized_entity() };
+            true
+        });
+        self.spawned_entities(spawned)
+    }
+
+    /// Creates entities in consecutive rows of the archetype storing exactly [`G`],
+    /// reserving capacity for `additional` entities up front.
+    /// [`push`] is called for each row and pushes it to the archetype, its entity handle is set afterwards.
+    /// Stops when [`push`] returns false without pushing a row, or once no more entities can be created.
+    /// Returns the index of the archetype and the range of rows of the created entities,
+    /// or None if the archetype could not be created.
+    /// Panics in case of allocation failure.
+    fn spawn_rows<G, P>(&mut self, additional: u32, mut push: P) -> Option<(u16, Range<u32>)>
+    where
+        G: ComponentGroup,
+        P: FnMut(&mut Archetype) -> bool,
+    {
+        let (archetype_index, archetype) = self
+            .archetypes
+            .find_or_create_archetype(G::DESCRIPTOR.archetype())?;
+        archetype.reserve(additional);
+        let first = archetype.len();
+        // Checked before pushing, so every pushed row gets an entity.
+        while !archetype.is_at_entity_limit() && self.entities.can_register_new_entity() {
+            let index_in_archetype = IndexInArchetype::new(archetype.len()).unwrap();
+            if !push(archetype) {
+                break;
+            }
+            let entity = self
+                .entities
+                .create_entity(
+                    index_in_archetype,
+                    ArchetypeIndex::new(archetype_index).unwrap(),
+                )
+                .unwrap();
+            archetype.entities_mut()[index_in_archetype.value() as usize] = entity;
+        }
+        archetype.mark_structural_change(self.change_tick.increment());
+        Some((archetype_index, first..archetype.len()))
+    }
+
+    /// Returns the entities created by [`Registry::spawn_rows`].
+    fn spawned_entities(&self, spawned: Option<(u16, Range<u32>)>) -> Vec<Entity> {
+        match spawned {
+            Some((archetype_index, rows)) => self.archetypes[archetype_index].entities()
+                [rows.start as usize..rows.end as usize]
+                .to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes the entity from the registry, dropping its components.
+    /// Counterpart of [`Registry::spawn`], equivalent to [`Registry::destroy_entity`].
+    /// Returns false if the entity is invalid or was removed already.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.destroy_entity(entity)
     }
 
     /// Removes the entity from the registry.
@@ -47,6 +293,7 @@ impl Registry {
         };
         let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
         let index_in_archetype = entry.index_in_archetype();
+        archetype.mark_structural_change(self.change_tick.increment());
         unsafe {
             if archetype.swap_drop_unchecked(index_in_archetype.value()) {
                 // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
@@ -65,6 +312,68 @@ impl Registry {
         true
     }
 
+    /// Swaps the rows of two entities stored in the same archetype, changing their iteration order.
+    /// Returns false if either entity is invalid or the entities are stored in different archetypes.
+    /// Swapping an entity with itself does nothing and returns true.
+    pub fn swap_rows(&mut self, a: Entity, b: Entity) -> bool {
+        let (first, second) = match (self.entities.entity_entry(a), self.entities.entity_entry(b)) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return false,
+        };
+        if first.archetype_index() != second.archetype_index() {
+            return false;
+        }
+        let first_index = first.index_in_archetype();
+        let second_index = second.index_in_archetype();
+        if first_index == second_index {
+            return true;
+        }
+        let archetype = unsafe { self.archetypes.get_unchecked_mut(first.archetype_index()) };
+        archetype.mark_structural_change(self.change_tick.increment());
+        unsafe { archetype.swap_entities(first_index.value(), second_index.value()) };
+        self.entities
+            .entity_entry_mut(a)
+            .unwrap()
+            .set_index_in_archetype(second_index);
+        self.entities
+            .entity_entry_mut(b)
+            .unwrap()
+            .set_index_in_archetype(first_index);
+        true
+    }
+
+    /// Returns the amount of entities moved between each pair of archetypes,
+    /// as (source archetype, destination archetype, moves), sorted by archetype ids.
+    /// Counts moves caused by adding or removing components.
+    /// Useful for finding entities which keep bouncing between the same archetypes.
+    #[cfg(feature = "stats")]
+    pub fn transition_stats(&self) -> Vec<(ArchetypeId, ArchetypeId, u64)> {
+        self.transition_counts
+            .iter()
+            .map(|(&(from, to), &count)| (from, to, count))
+            .collect()
+    }
+
+    /// Returns a guard which records structural changes and applies them once it is dropped or flushed.
+    /// The registry can be read through the guard while recording.
+    pub fn scoped_commands(&mut self) -> ScopedCommands<'_> {
+        ScopedCommands::new(self)
+    }
+
+    /// Returns an iterator which removes every entity stored in the archetype matching [`G`] exactly,
+    /// yielding the entity handles along with their components.
+    /// The archetype is empty once the iterator is consumed or dropped,
+    /// entities which were not yielded are dropped along with the iterator.
+    pub fn drain_archetype<G: ComponentGroup>(&mut self) -> impl Iterator<Item = (Entity, G)> + '_ {
+        let mut archetype = self
+            .archetypes
+            .find_archetype_mut(G::DESCRIPTOR.archetype());
+        if let Some(archetype) = archetype.as_mut() {
+            archetype.mark_structural_change(self.change_tick.increment());
+        }
+        DrainArchetype::<G>::new(archetype, &mut self.entities)
+    }
+
     /// Removes the entity from the registry if it matches the given component group exactly.
     /// Otherwise, it simply leaves the entity as is.
     /// This function return None if either entity given is invalid, or does not match the given component group.
@@ -74,28 +383,27 @@ impl Registry {
             Some(v) => v,
         };
         let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
+        if archetype.descriptor().len() != G::DESCRIPTOR.len()
+            || !archetype
+                .descriptor()
+                .contains_subset(G::DESCRIPTOR.archetype())
+        {
+            return None;
+        }
         let index_in_archetype = entry.index_in_archetype();
-        unsafe {
-            return match archetype.swap_remove_unchecked::<G>(index_in_archetype.value()) {
-                (value, true) => {
-                    // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
-                    // We retrieve the entity handle using the metadata, which is now at the old entity's position.
-                    let swapped_entity = archetype.entities()[index_in_archetype.value() as usize];
-                    self.entities
-                        .entity_entry_mut(swapped_entity)
-                        .unwrap()
-                        .set_index_in_archetype(index_in_archetype);
-                    let _v = self.entities.destroy_entity(entity);
-                    debug_assert!(_v);
-                    Some(value)
-                }
-                (value, false) => {
-                    let _v = self.entities.destroy_entity(entity);
-                    debug_assert!(_v);
-                    Some(value)
-                }
-            };
+        archetype.mark_structural_change(self.change_tick.increment());
+        let (value, swapped_entity) =
+            unsafe { archetype.swap_remove_unchecked::<G>(index_in_archetype.value()) };
+        if let Some(swapped_entity) = swapped_entity {
+            // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
+            self.entities
+                .entity_entry_mut(swapped_entity)
+                .unwrap()
+                .set_index_in_archetype(index_in_archetype);
         }
+        let _v = self.entities.destroy_entity(entity);
+        debug_assert!(_v);
+        Some(value)
     }
 
     /// Returns true if a given entity has the specified component.
@@ -109,6 +417,18 @@ impl Registry {
         archetype.descriptor().has_component::<C>()
     }
 
+    /// Returns true if a given entity has the specified component, like [`Registry::has_component`].
+    /// Uses a linear scan of the components of the entity's archetype instead of a binary search,
+    /// which is faster for archetypes with few components.
+    pub fn contains_fast<C: Component>(&self, entity: Entity) -> bool {
+        let entry = match self.entities.entity_entry(entity) {
+            None => return false,
+            Some(v) => v,
+        };
+        let archetype = unsafe { self.archetypes.get_unchecked(entry.archetype_index()) };
+        archetype.descriptor().contains_component_type(C::ID)
+    }
+
     /// Returns true if a given entity has all of the specified components.
     /// Returns false if entity is invalid or does not have all of the specified components.
     /// If you need to check for only a single components, prefer to use [`Registry::has_component`] instead.
@@ -123,6 +443,33 @@ impl Registry {
             .contains_subset(G::DESCRIPTOR.archetype())
     }
 
+    /// Returns whether writing the given value to component [`C`] of the entity would change it.
+    /// Compares using [`PartialEq`] without writing anything.
+    /// [`Component::EQ_HANDLER`] provides the same comparison for type erased components.
+    /// Returns None if the entity is invalid or does not have the specified component.
+    pub fn would_change<C: PartialEq + Component>(
+        &self,
+        entity: Entity,
+        component: &C,
+    ) -> Option<bool> {
+        let current = self.get_component::<C>(entity)?;
+        Some(*current != *component)
+    }
+
+    /// Returns a reference to the specified component if the entity has it.
+    /// Shorthand for [`Registry::get_component`].
+    /// Returns None if entity is invalid or does not have the specified component.
+    pub fn get<C: Component>(&self, entity: Entity) -> Option<&C> {
+        self.get_component::<C>(entity)
+    }
+
+    /// Returns a mutable reference to the specified component if the entity has it.
+    /// Shorthand for [`Registry::get_component_mut`].
+    /// Returns None if entity is invalid or does not have the specified component.
+    pub fn get_mut<C: Component>(&mut self, entity: Entity) -> Option<&mut C> {
+        self.get_component_mut::<C>(entity)
+    }
+
     /// Returns a reference to the specified component if the entity has it.
     /// Returns false if entity is invalid or does not have the specified component.
     pub fn get_component<C: Component>(&self, entity: Entity) -> Option<&C> {
@@ -140,6 +487,25 @@ impl Registry {
         .into()
     }
 
+    /// Returns a reference to the specified component, like [`Registry::get_component`].
+    /// Returns Err with the reason if the component cannot be accessed.
+    pub fn try_get_component<C: Component>(&self, entity: Entity) -> Result<&C, AccessError> {
+        if entity == Entity::INVALID {
+            return Err(AccessError::NullEntity);
+        }
+        let entry = self
+            .entities
+            .entity_entry(entity)
+            .ok_or(AccessError::DeadEntity)?;
+        unsafe {
+            let archetype = self.archetypes.get_unchecked(entry.archetype_index());
+            if !archetype.descriptor().has_component::<C>() {
+                return Err(AccessError::ComponentNotPresent);
+            }
+            Ok(archetype.get_component_unchecked::<C>(entry.index_in_archetype().value()))
+        }
+    }
+
     /// Returns a tuple of references to the specified components if the entity has all of them.
     /// Returns false if entity is invalid or does not have the specified components.
     /// If you need to get only a single component, use [`Registry::get_component`] instead.
@@ -177,11 +543,70 @@ impl Registry {
             if !archetype.descriptor().has_component::<C>() {
                 return None;
             }
+            archetype.mark_write(self.change_tick.increment());
             archetype.get_component_unchecked_mut::<C>(entry.index_in_archetype().value())
         }
         .into()
     }
 
+    /// Returns a reference bundling shared access to the components of the entity.
+    /// The entity's location is resolved once, which makes repeated accesses cheaper.
+    /// Returns None if the entity is invalid.
+    pub fn entity_ref(&self, entity: Entity) -> Option<EntityRef<'_>> {
+        let entry = self.entities.entity_entry(entity)?;
+        let archetype = unsafe { self.archetypes.get_unchecked(entry.archetype_index()) };
+        Some(EntityRef::new(
+            entity,
+            archetype,
+            entry.index_in_archetype().value(),
+        ))
+    }
+
+    /// Returns a reference bundling mutable access to the components of the entity.
+    /// The entity's location is resolved once, which makes repeated accesses cheaper.
+    /// Returns None if the entity is invalid.
+    pub fn entity_ref_mut(&mut self, entity: Entity) -> Option<EntityRefMut<'_>> {
+        let entry = self.entities.entity_entry(entity)?;
+        let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
+        Some(EntityRefMut::new(
+            entity,
+            archetype,
+            entry.index_in_archetype().value(),
+            &mut self.change_tick,
+        ))
+    }
+
+    /// Calls [`f`] with a mutable reference to component [`C`] of each of the given entities, in order.
+    /// Entities which are invalid or do not have the component are skipped,
+    /// entities provided multiple times are processed multiple times.
+    pub fn get_component_mut_each<C: Component, F: FnMut(Entity, &mut C)>(
+        &mut self,
+        entities: &[Entity],
+        mut f: F,
+    ) {
+        for entity in entities {
+            if let Some(component) = self.get_component_mut::<C>(*entity) {
+                f(*entity, component);
+            }
+        }
+    }
+
+    /// Returns a raw pointer to the component of the given entity.
+    /// Returns None if entity is invalid or does not have the specified component.
+    /// The entity is resolved safely, only dereferencing the pointer is unsafe.
+    ///
+    /// # Safety
+    /// - The pointer is only valid until the registry is next modified structurally,
+    ///   i.e. any entity creation, destruction or addition/removal of components may invalidate it.
+    /// - The registry must not be dropped while the pointer is in use.
+    /// - No other reference to the same component may be alive while the pointer is dereferenced.
+    /// - The pointer must not be used to move the component out of the registry,
+    ///   as the registry will still drop it.
+    pub fn component_ptr_mut<C: Component>(&mut self, entity: Entity) -> Option<*mut C> {
+        self.get_component_mut::<C>(entity)
+            .map(|component| component as *mut C)
+    }
+
     /// Returns a tuple of mutable references to the specified components if the entity has all of them.
     /// Returns false if entity is invalid or does not have the specified components.
     pub fn get_components_mut<'registry, G: ComponentGroup>(
@@ -200,21 +625,74 @@ impl Registry {
             {
                 return None;
             }
+            archetype.mark_write(self.change_tick.increment());
             archetype.get_fuzzy_components_unchecked_mut::<G>(entry.index_in_archetype().value())
         }
         .into()
     }
 
+    /// Overwrites the component with the given type id of the entity with the provided bytes.
+    /// The previous value of the component is dropped.
+    /// Reasons for failure:
+    /// - Invalid entity provided.
+    /// - The entity does not have the component.
+    /// - The amount of bytes does not match the size of the component.
+    ///
+    /// # Safety
+    /// - [`bytes`] must be a valid instance of the component.
+    /// - The registry takes ownership of the instance, it must not be dropped elsewhere.
+    pub unsafe fn apply_raw_component(
+        &mut self,
+        entity: Entity,
+        component_type_id: ComponentTypeId,
+        bytes: &[u8],
+    ) -> Result<(), ApplyError> {
+        let entry = self
+            .entities
+            .entity_entry(entity)
+            .ok_or(ApplyError::InvalidEntity)?;
+        let archetype = self.archetypes.get_unchecked_mut(entry.archetype_index());
+        let component = archetype
+            .descriptor()
+            .components()
+            .iter()
+            .find(|component| component.component_type_id() == component_type_id)
+            .ok_or(ApplyError::MissingComponent)?;
+        if bytes.len() != component.size() as usize {
+            return Err(ApplyError::SizeMismatch {
+                expected: component.size() as usize,
+                actual: bytes.len(),
+            });
+        }
+        archetype.mark_write(self.change_tick.increment());
+        archetype.write_component_bytes_unchecked(
+            entry.index_in_archetype().value(),
+            component_type_id,
+            bytes,
+        );
+        Ok(())
+    }
+
     /// Adds a given component to the entity if it's not yet present.
-    /// Returns the original component in case of failure for any reason.
+    /// Returns the original component inside the error in case of failure.
     /// Reasons for failure:
     /// - Invalid entity provided.
-    /// - Destination archetype could not be created.
-    pub fn add_component<C: Component>(&mut self, entity: Entity, component: C) -> Result<(), C> {
+    /// - The entity already has the component, which is left unchanged.
+    /// - Destination archetype could not be created or is full.
+    pub fn add_component<C: Component>(
+        &mut self,
+        entity: Entity,
+        component: C,
+    ) -> Result<(), AddComponentError<C>> {
         let entry = match self.entities.entity_entry(entity) {
-            None => return Err(component),
+            None => return Err(AddComponentError::InvalidEntity(component)),
             Some(v) => v.clone(),
         };
+        let source_descriptor =
+            unsafe { self.archetypes.get_unchecked(entry.archetype_index()) }.descriptor();
+        if source_descriptor.has_component::<C>() {
+            return Err(AddComponentError::AlreadyPresent(component));
+        }
 
         // Get the new archetype
         let (source_archetype, destination_archetype_index, destination_archetype) = match self
@@ -222,67 +700,92 @@ impl Registry {
             .find_or_create_archetype_adding_component(entry.archetype_index(), &C::DESCRIPTOR)
         {
             Some(v) => v,
-            None => return Err(component),
+            None => return Err(AddComponentError::LimitExceeded(component)),
         };
-
-        // Make sure the entity we move is at the end of it's archetype (so data stays contiguous).
-        if unsafe { source_archetype.swap_to_last_unchecked(entry.index_in_archetype().value()) } {
-            // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
-            // We retrieve the entity handle using the metadata, which is now at the swapped with entity's position.
-            let swapped_entity =
-                source_archetype.entities()[entry.index_in_archetype().value() as usize];
-            self.entities
-                .entity_entry_mut(swapped_entity)
-                .unwrap()
-                .set_index_in_archetype(entry.index_in_archetype());
+        if destination_archetype.is_at_entity_limit() {
+            return Err(AddComponentError::LimitExceeded(component));
         }
 
+        let tick = self.change_tick.increment();
+        source_archetype.mark_structural_change(tick);
+        destination_archetype.mark_structural_change(tick);
+        #[cfg(feature = "stats")]
+        let transition = (
+            source_archetype.descriptor().archetype_id(),
+            destination_archetype.descriptor().archetype_id(),
+        );
+
         unsafe {
-            // Make space in the destination archetype.
-            destination_archetype.push_uninitialized_entity();
-
-            // copy from end to end.
-            let new_source_entity_index_in_archetype = source_archetype.len() - 1;
-            let destination_entity_index_in_archetype = destination_archetype.len() - 1;
-            // Write common components.
-            Archetype::copy_common_components_between_archetypes_unchecked(
-                source_archetype,
-                new_source_entity_index_in_archetype,
-                destination_archetype,
-                destination_entity_index_in_archetype,
-            );
+            let (destination_index, moved_entity) = source_archetype
+                .move_row_to(entry.index_in_archetype().value(), destination_archetype);
             // Write added component
-            destination_archetype
-                .write_single_component_unchecked(destination_entity_index_in_archetype, component);
-
-            // Copy the metadata
-            destination_archetype.entities_mut()[destination_entity_index_in_archetype as usize] =
-                source_archetype.entities()[new_source_entity_index_in_archetype as usize];
-
-            // Make the source archetype forget the old entity.
-            source_archetype.decrement_len_unchecked();
-
-            // Update the original entity entry to point to destination archetype and index in archetype.
-            let entity_entry = self.entities.entity_entry_mut(entity).unwrap();
-            entity_entry.set_archetype_index(destination_archetype_index);
-            entity_entry.set_index_in_archetype(
-                IndexInArchetype::new(destination_entity_index_in_archetype).unwrap(),
+            destination_archetype.write_single_component_unchecked(destination_index, component);
+            self.update_moved_entities(
+                entity,
+                destination_archetype_index,
+                destination_index,
+                moved_entity,
+                entry.index_in_archetype(),
             );
-
+            #[cfg(feature = "stats")]
+            self.record_transition(transition, 1);
             Ok(())
         }
     }
 
+    /// Returns a mutable reference to the component of the given entity.
+    /// If the entity does not have the component, the result of [`f`] is added to the entity first.
+    /// In that case the entity is moved to a different archetype, the reference points into that archetype.
+    /// Returns None if the entity is invalid or if the component could not be added.
+    pub fn get_component_or_insert_with<C: Component, F: FnOnce() -> C>(
+        &mut self,
+        entity: Entity,
+        f: F,
+    ) -> Option<&mut C> {
+        self.entities.entity_entry(entity)?;
+        if !self.has_component::<C>(entity) {
+            self.add_component(entity, f()).ok()?;
+        }
+        self.get_component_mut::<C>(entity)
+    }
+
     /// Removes a given component from the entity if it's present.
-    /// Returns the component in if successful.
+    /// Returns the component if successful.
+    /// If the component is the last component of the entity, the entity is removed from the registry.
     /// Reasons for failure:
     /// - Invalid entity provided.
+    /// - The entity does not have the component.
     /// - Destination archetype could not be created.
-    pub fn remove_component<C: Component>(&mut self, entity: Entity) -> Result<C, ()> {
+    pub fn remove_component<C: Component>(
+        &mut self,
+        entity: Entity,
+    ) -> Result<C, RemoveComponentError> {
         let entry = match self.entities.entity_entry(entity) {
-            None => return Err(()),
+            None => return Err(RemoveComponentError::InvalidEntity),
             Some(v) => v.clone(),
         };
+        let source_descriptor =
+            unsafe { self.archetypes.get_unchecked(entry.archetype_index()) }.descriptor();
+        if !source_descriptor.has_component::<C>() {
+            return Err(RemoveComponentError::MissingComponent);
+        }
+        if source_descriptor.len() == 1 {
+            // Entities cannot exist without components, so the entity is removed entirely.
+            let archetype = unsafe { self.archetypes.get_unchecked_mut(entry.archetype_index()) };
+            let index_in_archetype = entry.index_in_archetype();
+            archetype.mark_structural_change(self.change_tick.increment());
+            let (component, swapped_entity) =
+                unsafe { archetype.swap_remove_unchecked::<C>(index_in_archetype.value()) };
+            if let Some(swapped_entity) = swapped_entity {
+                self.entities
+                    .entity_entry_mut(swapped_entity)
+                    .unwrap()
+                    .set_index_in_archetype(index_in_archetype);
+            }
+            let _v = self.entities.destroy_entity(entity);
+            debug_assert!(_v);
+            return Ok(component);
+        }
 
         // Get the new archetype
         let (source_archetype, destination_archetype_index, destination_archetype) = match self
@@ -290,55 +793,337 @@ impl Registry {
             .find_or_create_archetype_removing_component(entry.archetype_index(), &C::DESCRIPTOR)
         {
             Some(v) => v,
-            None => return Err(()),
+            None => return Err(RemoveComponentError::LimitExceeded),
         };
-
-        // Make sure the entity we move is at the end of it's archetype (so data stays contiguous).
-        if unsafe { source_archetype.swap_to_last_unchecked(entry.index_in_archetype().value()) } {
-            // A swap was needed, so we need to update the index_in_archetype of the entry that it was swapped with.
-            // We retrieve the entity handle using the metadata, which is now at the swapped with entity's position.
-            let swapped_entity =
-                source_archetype.entities()[entry.index_in_archetype().value() as usize];
-            self.entities
-                .entity_entry_mut(swapped_entity)
-                .unwrap()
-                .set_index_in_archetype(entry.index_in_archetype());
+        if destination_archetype.is_at_entity_limit() {
+            return Err(RemoveComponentError::LimitExceeded);
         }
 
+        let tick = self.change_tick.increment();
+        source_archetype.mark_structural_change(tick);
+        destination_archetype.mark_structural_change(tick);
+        #[cfg(feature = "stats")]
+        let transition = (
+            source_archetype.descriptor().archetype_id(),
+            destination_archetype.descriptor().archetype_id(),
+        );
+
         unsafe {
-            // Make space in the destination archetype.
-            destination_archetype.push_uninitialized_entity();
-
-            // copy from end to end.
-            let new_source_entity_index_in_archetype = source_archetype.len() - 1;
-            let destination_entity_index_in_archetype = destination_archetype.len() - 1;
-            // Write common components.
-            Archetype::copy_common_components_between_archetypes_unchecked(
-                source_archetype,
-                new_source_entity_index_in_archetype,
-                destination_archetype,
-                destination_entity_index_in_archetype,
-            );
             // Read removed component
             let component: C =
-                source_archetype.read_component_unchecked(new_source_entity_index_in_archetype);
+                source_archetype.read_component_unchecked(entry.index_in_archetype().value());
+            let (destination_index, moved_entity) = source_archetype
+                .move_row_to(entry.index_in_archetype().value(), destination_archetype);
+            self.update_moved_entities(
+                entity,
+                destination_archetype_index,
+                destination_index,
+                moved_entity,
+                entry.index_in_archetype(),
+            );
+            #[cfg(feature = "stats")]
+            self.record_transition(transition, 1);
+            Ok(component)
+        }
+    }
+
+    /// Replaces component [`Old`] by component [`New`] on every entity which has it,
+    /// converting each value using [`f`].
+    /// Entities which already have [`New`] keep only the converted value, their previous [`New`] is dropped.
+    /// Entities stay unchanged if their destination archetype cannot be created or is full.
+    /// Does nothing if both components have the same component type id.
+    /// Aborts if [`f`] panics, as the registry cannot be restored in that case.
+    pub fn migrate<Old: Component, New: Component, F: Fn(Old) -> New>(&mut self, f: F) {
+        struct AbortOnUnwind;
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                // Panicking while unwinding aborts.
+                panic!("Component conversion panicked during Registry::migrate.");
+            }
+        }
+
+        if <Old as Component>::ID == <New as Component>::ID {
+            return;
+        }
+        for archetype_id in self.non_empty_archetype_ids_matching::<Old>() {
+            self.transfer_archetype(
+                archetype_id,
+                |archetypes, index| {
+                    archetypes.find_or_create_archetype_replacing_component(
+                        index,
+                        &Old::DESCRIPTOR,
+                        &New::DESCRIPTOR,
+                    )
+                },
+                |source_archetype, destination_archetype| {
+                    let replaces_existing = source_archetype.descriptor().has_component::<New>();
+                    let first = destination_archetype.len();
+                    let count = source_archetype.len();
+                    // Move from the end, so no other entities need to be swapped into place.
+                    while source_archetype.len() > 0 {
+                        let index = source_archetype.len() - 1;
+                        unsafe {
+                            let old: Old = source_archetype.read_component_unchecked(index);
+                            // The source archetype still owns the old value until the row is moved.
+                            let guard = AbortOnUnwind;
+                            let new = f(old);
+                            core::mem::forget(guard);
+
+                            let (destination_index, _) =
+                                source_archetype.move_row_to(index, destination_archetype);
+                            if replaces_existing {
+                                *destination_archetype
+                                    .get_component_unchecked_mut::<New>(destination_index) = new;
+                            } else {
+                                destination_archetype
+                                    .write_single_component_unchecked(destination_index, new);
+                            }
+                        }
+                    }
+                    (first, count)
+                },
+            );
+        }
+    }
+
+    /// Removes component [`C`] from every entity which has it, dropping the removed values.
+    /// All entities of an archetype are moved to the archetype without [`C`] at once.
+    /// Like [`Registry::remove_component`], entities which only have [`C`] are removed from the registry.
+    /// Entities stay unchanged if their destination archetype cannot be created or is full.
+    /// Returns the amount of entities from which [`C`] was removed, including removed entities.
+    pub fn strip_component<C: Component>(&mut self) -> u32 {
+        self.remove_component_from_all::<C>(None)
+    }
+
+    /// Removes component [`C`] from every entity which has it, like [`Registry::strip_component`],
+    /// returning the removed values along with their entities instead of dropping them.
+    /// The returned pairs are grouped by archetype.
+    /// Like [`Registry::remove_component`], entities which only have [`C`] are removed from the registry.
+    /// Entities stay unchanged if their destination archetype cannot be created or is full.
+    pub fn extract_components<C: Component>(&mut self) -> Vec<(Entity, C)> {
+        let mut extracted = Vec::new();
+        self.remove_component_from_all::<C>(Some(&mut extracted));
+        extracted
+    }
+
+    /// Adds component [`C`] to every entity matching [`G`], computing each value from the entity's components using [`f`].
+    /// All entities of an archetype are moved to the archetype with [`C`] at once.
+    /// Entities which already have [`C`] are skipped.
+    /// Entities also stay unchanged if their destination archetype cannot be created or is full.
+    /// If [`f`] panics, the entities of the archetype being processed are left unchanged.
+    /// Returns the amount of entities to which [`C`] was added.
+    pub fn add_component_to_matching<G, C, F>(&mut self, mut f: F) -> u32
+    where
+        G: ComponentGroup,
+        C: Component,
+        F: for<'c> FnMut(G::RefTuple<'c>) -> C,
+    {
+        let mut affected = 0;
+        for archetype_id in self.non_empty_archetype_ids_matching::<G>() {
+            let source_archetype = self.archetypes.find_archetype_by_id(archetype_id).unwrap();
+            if source_archetype.descriptor().has_component::<C>() {
+                continue;
+            }
+            // Computed up front, so a panic in f does not leave rows without a component.
+            let components = (0..source_archetype.len())
+                .map(|index| unsafe {
+                    f(source_archetype.get_fuzzy_components_unchecked::<G>(index))
+                })
+                .collect::<Vec<_>>();
+            affected += self
+                .transfer_archetype(
+                    archetype_id,
+                    |archetypes, index| {
+                        archetypes.find_or_create_archetype_adding_component(index, &C::DESCRIPTOR)
+                    },
+                    |source_archetype, destination_archetype| unsafe {
+                        let (first, count) =
+                            source_archetype.move_all_rows_to(destination_archetype);
+                        for (index, component) in (first..first + count).zip(components) {
+                            destination_archetype
+                                .write_single_component_unchecked(index, component);
+                        }
+                        (first, count)
+                    },
+                )
+                .unwrap_or(0);
+        }
+        affected
+    }
 
-            // Copy the metadata
-            destination_archetype.entities_mut()[destination_entity_index_in_archetype as usize] =
-                source_archetype.entities()[new_source_entity_index_in_archetype as usize];
+    /// Returns the ids of all archetypes matching [`G`] which contain entities.
+    fn non_empty_archetype_ids_matching<G: ComponentGroup>(&self) -> Vec<ArchetypeId> {
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .filter(|archetype| archetype.len() > 0)
+            .map(|archetype| archetype.descriptor().archetype_id())
+            .collect()
+    }
 
-            // Make the source archetype forget the old entity.
-            source_archetype.decrement_len_unchecked();
+    /// Removes component [`C`] from every entity which has it, see [`Registry::strip_component`].
+    /// The removed values are appended to [`extracted`] along with their entities, or dropped if it is None.
+    /// Returns the amount of entities from which [`C`] was removed, including removed entities.
+    fn remove_component_from_all<C: Component>(
+        &mut self,
+        mut extracted: Option<&mut Vec<(Entity, C)>>,
+    ) -> u32 {
+        let mut values = Vec::new();
+        let mut affected = 0;
+        for archetype_id in self.non_empty_archetype_ids_matching::<C>() {
+            let archetype_index = self
+                .archetypes
+                .find_archetype_index_by_id(archetype_id)
+                .unwrap();
+            let archetype = unsafe { self.archetypes.get_unchecked_mut(archetype_index) };
+            if archetype.descriptor().len() == 1 {
+                // Entities cannot exist without components, so the entities are removed entirely.
+                archetype.mark_structural_change(self.change_tick.increment());
+                affected += archetype.len();
+                // Removed from the end, so no other entities need to be swapped into place.
+                while archetype.len() > 0 {
+                    let entity = archetype.entities()[archetype.len() as usize - 1];
+                    let (component, _) =
+                        unsafe { archetype.swap_remove_unchecked::<C>(archetype.len() - 1) };
+                    let _v = self.entities.destroy_entity(entity);
+                    debug_assert!(_v);
+                    if let Some(extracted) = extracted.as_deref_mut() {
+                        extracted.push((entity, component));
+                    }
+                }
+                continue;
+            }
+            affected += self
+                .transfer_archetype(
+                    archetype_id,
+                    |archetypes, index| {
+                        archetypes
+                            .find_or_create_archetype_removing_component(index, &C::DESCRIPTOR)
+                    },
+                    |source_archetype, destination_archetype| unsafe {
+                        let Some(extracted) = extracted.as_deref_mut() else {
+                            return source_archetype.move_all_rows_to(destination_archetype);
+                        };
+                        let (first, count) = source_archetype
+                            .move_all_rows_to_extracting(destination_archetype, &mut values);
+                        let entities = &destination_archetype.entities()[first as usize..];
+                        extracted.extend(entities.iter().copied().zip(values.drain(..)));
+                        (first, count)
+                    },
+                )
+                .unwrap_or(0);
+        }
+        affected
+    }
+
+    /// Moves all entities of the archetype with [`source_archetype_id`] to another archetype at once.
+    /// [`destination`] returns the source archetype, the index of the destination archetype and
+    /// the destination archetype, given the archetype registry and the index of the source archetype.
+    /// [`transfer`] moves all rows of the source archetype to the end of the destination archetype,
+    /// returning the index of the first new row and the amount of moved rows like [`Archetype::move_all_rows_to`].
+    /// Updates the entries of the moved entities and records the transition.
+    /// Returns the amount of moved entities, or None if the destination archetype cannot be created or is full.
+    fn transfer_archetype<D, T>(
+        &mut self,
+        source_archetype_id: ArchetypeId,
+        destination: D,
+        transfer: T,
+    ) -> Option<u32>
+    where
+        D: for<'a> FnOnce(
+            &'a mut ArchetypeRegistry,
+            u16,
+        ) -> Option<(&'a mut Archetype, u16, &'a mut Archetype)>,
+        T: FnOnce(&mut Archetype, &mut Archetype) -> (u32, u32),
+    {
+        let source_archetype_index = self
+            .archetypes
+            .find_archetype_index_by_id(source_archetype_id)?;
+        let (source_archetype, destination_archetype_index, destination_archetype) =
+            destination(&mut self.archetypes, source_archetype_index)?;
+        if destination_archetype.len() as u64 + source_archetype.len() as u64
+            >= MAX_ENTITIES_PER_ARCHETYPE as u64
+        {
+            return None;
+        }
+        let tick = self.change_tick.increment();
+        source_archetype.mark_structural_change(tick);
+        destination_archetype.mark_structural_change(tick);
+        #[cfg(feature = "stats")]
+        let transition = (
+            source_archetype_id,
+            destination_archetype.descriptor().archetype_id(),
+        );
 
-            // Update the original entity entry to point to destination archetype and index in archetype.
+        let (first, count) = transfer(source_archetype, destination_archetype);
+        for index in first..first + count {
+            let entity = destination_archetype.entities()[index as usize];
             let entity_entry = self.entities.entity_entry_mut(entity).unwrap();
             entity_entry.set_archetype_index(destination_archetype_index);
-            entity_entry.set_index_in_archetype(
-                IndexInArchetype::new(destination_entity_index_in_archetype).unwrap(),
-            );
+            entity_entry.set_index_in_archetype(IndexInArchetype::new(index).unwrap());
+        }
+        #[cfg(feature = "stats")]
+        self.record_transition(transition, count as u64);
+        Some(count)
+    }
 
-            Ok(component)
+    /// Removes rows of archetypes which are not referenced by a valid entity, for example after recovering
+    /// from a partially written state. Remaining rows are moved into the gaps and their entities are updated.
+    /// The components of removed rows are considered invalid and are not dropped.
+    /// Returns the amount of removed rows.
+    pub fn compact(&mut self) -> u32 {
+        let entities = &mut self.entities;
+        let mut removed = 0;
+        for (archetype_index, archetype) in self.archetypes.iter_archetypes_mut().enumerate() {
+            let archetype_index = archetype_index as u16;
+            let stored = archetype.entities();
+            let is_live = |index: u32| {
+                entities
+                    .entity_entry(stored[index as usize])
+                    .is_some_and(|entry| {
+                        entry.archetype_index() == archetype_index
+                            && entry.index_in_archetype().value() == index
+                    })
+            };
+            let live = (0..archetype.len())
+                .filter(|index| is_live(*index))
+                .collect::<Vec<_>>();
+            if live.len() == archetype.len() as usize {
+                continue;
+            }
+            removed += unsafe { archetype.compact(|index| live.binary_search(&index).is_ok()) };
+            archetype.mark_structural_change(self.change_tick.increment());
+            for (index, entity) in archetype.entities().iter().enumerate() {
+                let entity_entry = entities.entity_entry_mut(*entity).unwrap();
+                entity_entry.set_index_in_archetype(IndexInArchetype::new(index as u32).unwrap());
+            }
+        }
+        removed
+    }
+
+    #[cfg(feature = "stats")]
+    fn record_transition(&mut self, transition: (ArchetypeId, ArchetypeId), moves: u64) {
+        *self.transition_counts.entry(transition).or_insert(0) += moves;
+    }
+
+    /// Updates the entries of an entity moved to a different archetype by [`Archetype::move_row_to`],
+    /// and of the entity which took its place in the source archetype.
+    fn update_moved_entities(
+        &mut self,
+        entity: Entity,
+        destination_archetype_index: u16,
+        destination_index: u32,
+        moved_entity: Option<Entity>,
+        source_index: IndexInArchetype,
+    ) {
+        if let Some(moved_entity) = moved_entity {
+            self.entities
+                .entity_entry_mut(moved_entity)
+                .unwrap()
+                .set_index_in_archetype(source_index);
         }
+        let entity_entry = self.entities.entity_entry_mut(entity).unwrap();
+        entity_entry.set_archetype_index(destination_archetype_index);
+        entity_entry.set_index_in_archetype(IndexInArchetype::new(destination_index).unwrap());
     }
 }
 
@@ -348,6 +1133,17 @@ impl Registry {
         self.entities.iter()
     }
 
+    /// Calls [`f`] with the archetype id, the row index and the entity handle of every entity,
+    /// in archetype and row order. Useful for building custom indices in a single traversal.
+    pub fn for_each_metadata<F: FnMut(ArchetypeId, u32, Entity)>(&self, mut f: F) {
+        for archetype in self.archetypes.iter_archetypes() {
+            let archetype_id = archetype.descriptor().archetype_id();
+            for (row, entity) in archetype.entities().iter().enumerate() {
+                f(archetype_id, row as u32, *entity);
+            }
+        }
+    }
+
     /// Returns an iterator which iterates over all components in archetypes
     /// matching the specified predicate.
     pub fn iter_components_matching<'registry, G: ComponentGroup>(
@@ -356,14 +1152,62 @@ impl Registry {
         self.archetypes.iter_components_matching::<G>()
     }
 
+    /// Returns an iterator which iterates over all components in archetypes
+    /// matching the specified predicate.
+    /// Never returns Err, as component groups with duplicate or too many components fail to compile.
+    /// [`QueryError`] has no variants, so the result can be destructured using `let Ok(iter) = ...`.
+    pub fn try_query<'registry, G: ComponentGroup>(
+        &'registry self,
+    ) -> Result<
+        impl Iterator<Item = <G as ComponentGroup>::SliceRefTuple<'registry>> + 'registry,
+        QueryError,
+    > {
+        Ok(self.archetypes.iter_components_matching::<G>())
+    }
+
     /// Returns an iterator which mutably iterates over all components in archetypes
     /// matching the specified predicate.
     pub fn iter_components_matching_mut<'registry, G: ComponentGroup>(
         &'registry mut self,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'registry>> + 'registry {
+        self.mark_written_matching::<G>(|_| true);
         self.archetypes.iter_components_matching_mut::<G>()
     }
 
+    /// Returns an iterator over the columns of component [`C`] in all archetypes containing it.
+    /// Equivalent to [`Registry::iter_components_matching`] with just [`C`], without going through component groups.
+    pub fn iter_single<C: Component>(&self) -> impl Iterator<Item = &[C]> + '_ {
+        self.archetypes
+            .iter_archetypes_matching::<C>()
+            .filter_map(|archetype| archetype.column::<C>())
+    }
+
+    /// Returns an iterator over the mutable columns of component [`C`] in all archetypes containing it.
+    /// Equivalent to [`Registry::iter_components_matching_mut`] with just [`C`], without going through component groups.
+    pub fn iter_single_mut<C: Component>(&mut self) -> impl Iterator<Item = &mut [C]> + '_ {
+        self.mark_written_matching::<C>(|_| true);
+        self.archetypes
+            .iter_archetypes_matching_mut::<C>()
+            .filter_map(|archetype| archetype.column_mut::<C>())
+    }
+
+    /// Returns an iterator which yields the archetype id and all components of each archetype
+    /// matching the specified predicate, one item per archetype.
+    /// This is useful if processing needs to be aware of archetype boundaries.
+    pub fn query_segmented<'registry, G: ComponentGroup>(
+        &'registry self,
+    ) -> impl Iterator<Item = (ArchetypeId, <G as ComponentGroup>::SliceRefTuple<'registry>)> + 'registry
+    {
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .map(|archetype| unsafe {
+                (
+                    archetype.descriptor().archetype_id(),
+                    archetype.get_fuzzy_slices_unchecked::<G>(),
+                )
+            })
+    }
+
     /// Returns an iterator which iterates over all entities and components in archetypes
     /// matching the specified predicate.
     pub fn iter_entity_components_matching<'registry, G: ComponentGroup>(
@@ -387,6 +1231,7 @@ impl Registry {
             <G as ComponentGroup>::SliceMutRefTuple<'registry>,
         ),
     > + 'registry {
+        self.mark_written_matching::<G>(|_| true);
         self.archetypes.iter_entity_components_matching_mut::<G>()
     }
 
@@ -416,6 +1261,7 @@ impl Registry {
         &'registry mut self,
         filter_closure: F,
     ) -> impl Iterator<Item = <G as ComponentGroup>::SliceMutRefTuple<'registry>> + 'registry {
+        self.mark_written_matching::<G>(&filter_closure);
         self.archetypes
             .iter_filtered_components_matching_mut::<G, F>(filter_closure)
     }
@@ -456,10 +1302,54 @@ impl Registry {
             <G as ComponentGroup>::SliceMutRefTuple<'registry>,
         ),
     > + 'registry {
+        self.mark_written_matching::<G>(&filter_closure);
         self.archetypes
             .iter_filtered_entity_components_matching_mut::<G, F>(filter_closure)
     }
 
+    /// Returns the amount of entities in archetypes matching the specified predicate.
+    /// Archetypes not matching the filter closure are excluded.
+    /// Only visits archetypes, individual entities are not iterated.
+    pub fn count_filtered<G: ComponentGroup, F: Fn(&ArchetypeDescriptor) -> bool>(
+        &self,
+        filter_closure: F,
+    ) -> u32 {
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .filter(|archetype| filter_closure(archetype.descriptor()))
+            .map(|archetype| archetype.len())
+            .sum()
+    }
+
+    /// Returns the amount of archetypes containing all components in [`G`], including empty ones.
+    /// Together with the amount of matching entities this indicates whether a query visits
+    /// few large archetypes or many small ones. Entities are not iterated.
+    pub fn count_archetypes_matching<G: ComponentGroup>(&self) -> usize {
+        self.archetypes.iter_archetypes_matching::<G>().count()
+    }
+
+    /// Calls the closure for each entity in archetypes matching the specified predicate.
+    /// The closure receives the entity handle and mutable references to its components.
+    pub fn for_each_with_entity_mut<'registry, G: ComponentGroup, F>(&'registry mut self, mut f: F)
+    where
+        F: FnMut(Entity, <G as ComponentGroup>::MutRefTuple<'registry>),
+    {
+        self.mark_written_matching::<G>(|_| true);
+        for archetype in self.archetypes.iter_archetypes_matching_mut::<G>() {
+            let archetype = archetype as *mut Archetype;
+            for index in 0..unsafe { (*archetype).len() } {
+                // Safety: each index is visited exactly once, so the references never alias.
+                unsafe {
+                    let entity = *(*archetype).entities().get_unchecked(index as usize);
+                    f(
+                        entity,
+                        (*archetype).get_fuzzy_components_unchecked_mut::<G>(index),
+                    );
+                }
+            }
+        }
+    }
+
     /// Returns a tuple of component slices if the exact archetype
     /// matching the predicate exists.
     pub fn iter_components_exact<'registry, G: ComponentGroup>(
@@ -480,7 +1370,10 @@ impl Registry {
             .archetypes
             .find_archetype_mut(G::DESCRIPTOR.archetype())
         {
-            Some(v) => unsafe { v.get_slices_unchecked_exact_mut::<G>() },
+            Some(v) => unsafe {
+                v.mark_write(self.change_tick.increment());
+                v.get_slices_unchecked_exact_mut::<G>()
+            },
             None => G::empty_slice_mut(),
         }
     }
@@ -513,6 +1406,7 @@ impl Registry {
         {
             // Safety: entities is a separate slice, not being accessed in get_slices_unchecked_exact_mut.
             Some(v) => unsafe {
+                v.mark_write(self.change_tick.increment());
                 (
                     (*(v as *mut Archetype)).entities(),
                     v.get_slices_unchecked_exact_mut::<G>(),
@@ -521,4 +1415,727 @@ impl Registry {
             None => (&[], G::empty_slice_mut()),
         }
     }
+
+    /// Returns an iterator which yields the archetype id and all components of each archetype
+    /// matching the specified predicate, one item per archetype.
+    /// Only archetypes which changed structurally or were accessed mutably after the given tick are visited.
+    /// Mutable access is tracked per archetype, handing out mutable access counts as a write, even if nothing is written.
+    pub fn query_archetypes_changed_since<'registry, G: ComponentGroup>(
+        &'registry self,
+        tick: Tick,
+    ) -> impl Iterator<Item = (ArchetypeId, <G as ComponentGroup>::SliceRefTuple<'registry>)> + 'registry
+    {
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .filter(move |archetype| archetype.changed_since(tick))
+            .map(|archetype| unsafe {
+                (
+                    archetype.descriptor().archetype_id(),
+                    archetype.get_fuzzy_slices_unchecked::<G>(),
+                )
+            })
+    }
+
+    /// Returns an iterator which yields every entity and its components in [`G`],
+    /// for all archetypes matching [`G`] which changed after the given tick.
+    /// Changes are tracked per archetype, see [`Registry::query_archetypes_changed_since`].
+    pub fn changed_since<'registry, G: ComponentGroup>(
+        &'registry self,
+        tick: Tick,
+    ) -> impl Iterator<Item = (Entity, <G as ComponentGroup>::RefTuple<'registry>)> + 'registry
+    {
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .filter(move |archetype| archetype.changed_since(tick))
+            .flat_map(|archetype| {
+                archetype
+                    .entities()
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, entity)| unsafe {
+                        (
+                            *entity,
+                            archetype.get_fuzzy_components_unchecked::<G>(index as u32),
+                        )
+                    })
+            })
+    }
+
+    /// Calls [`f`] in parallel for chunks of at most `chunk` entities of every archetype matching [`G`],
+    /// with the entities and component slices of the chunk.
+    /// Large archetypes are split into many chunks, so work is balanced across threads.
+    /// A `chunk` of 0 is treated as 1.
+    #[cfg(feature = "parallel")]
+    pub fn par_query_chunked<G, F>(&self, chunk: usize, f: F)
+    where
+        G: ComponentGroup,
+        F: for<'c> Fn(&'c [Entity], G::SliceRefTuple<'c>) + Sync,
+    {
+        use rayon::prelude::*;
+
+        let chunk = chunk.clamp(1, MAX_ENTITIES_PER_ARCHETYPE as usize) as u32;
+        let chunks = self
+            .archetypes
+            .iter_archetypes_matching::<G>()
+            .flat_map(|archetype| {
+                (0..archetype.len())
+                    .step_by(chunk as usize)
+                    .map(move |first| (archetype, first, chunk.min(archetype.len() - first)))
+            })
+            .collect::<Vec<_>>();
+        chunks
+            .into_par_iter()
+            .for_each(|(archetype, first, len)| unsafe {
+                let entities = &archetype.entities()[first as usize..(first + len) as usize];
+                f(
+                    entities,
+                    archetype.get_fuzzy_slices_range_unchecked::<G>(first, len),
+                );
+            });
+    }
+
+    /// Maps the components of every entity matching [`G`] using [`map`] and combines the results using [`reduce`].
+    /// With the `parallel` feature, archetypes and chunks of entities are processed in parallel,
+    /// otherwise the entities are folded sequentially.
+    /// [`reduce`] must be associative and [`identity`] must be its identity element,
+    /// as the order in which results are combined is unspecified.
+    /// Returns [`identity`] if no entity matches [`G`].
+    pub fn par_reduce<G, T, M, R>(&self, identity: T, map: M, reduce: R) -> T
+    where
+        G: ComponentGroup,
+        T: Clone + Send + Sync,
+        M: for<'c> Fn(G::RefTuple<'c>) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+    {
+        let archetypes = self.archetypes.iter_archetypes_matching::<G>();
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            archetypes
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .flat_map(|archetype| {
+                    (0..archetype.len()).into_par_iter().fold(
+                        || identity.clone(),
+                        |accumulator, index| unsafe {
+                            reduce(
+                                accumulator,
+                                map(archetype.get_fuzzy_components_unchecked::<G>(index)),
+                            )
+                        },
+                    )
+                })
+                .reduce(|| identity.clone(), &reduce)
+        }
+        #[cfg(not(feature = "parallel"))]
+        archetypes.fold(identity, |accumulator, archetype| {
+            (0..archetype.len()).fold(accumulator, |accumulator, index| unsafe {
+                reduce(
+                    accumulator,
+                    map(archetype.get_fuzzy_components_unchecked::<G>(index)),
+                )
+            })
+        })
+    }
+
+    /// Returns an iterator over the entities and components matching [`G`] in the window `[skip, skip + take)`.
+    /// The window is taken over all matching entities, in the order of [`Registry::iter_entity_components_matching`],
+    /// so consecutive windows process every entity once as long as no structural changes happen in between.
+    pub fn query_range<'registry, G: ComponentGroup>(
+        &'registry self,
+        skip: usize,
+        take: usize,
+    ) -> impl Iterator<Item = (Entity, <G as ComponentGroup>::RefTuple<'registry>)> + 'registry
+    {
+        let end = skip.saturating_add(take);
+        // Amount of matching entities in the archetypes before the current one.
+        let mut offset = 0usize;
+        self.archetypes
+            .iter_archetypes_matching::<G>()
+            .flat_map(move |archetype| {
+                let first = offset;
+                let last = first + archetype.len() as usize;
+                offset = last;
+                let start = skip.clamp(first, last) - first;
+                let stop = end.clamp(first, last) - first;
+                (start..stop).map(move |index| unsafe {
+                    (
+                        archetype.entities()[index],
+                        archetype.get_fuzzy_components_unchecked::<G>(index as u32),
+                    )
+                })
+            })
+    }
+
+    /// Records mutable access at a new tick for all archetypes matching [`G`] and the filter closure.
+    fn mark_written_matching<G: ComponentGroup>(
+        &mut self,
+        filter_closure: impl Fn(&ArchetypeDescriptor) -> bool,
+    ) {
+        let tick = self.change_tick.increment();
+        self.archetypes
+            .iter_archetypes_matching_mut::<G>()
+            .filter(|archetype| filter_closure(archetype.descriptor()))
+            .for_each(|archetype| archetype.mark_write(tick));
+    }
+}
+
+impl Registry {
+    /// Returns the tick of the most recent change made to the registry.
+    /// Any change made afterwards has a greater tick.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick
+    }
+
+    /// Reserves capacity for at least `additional` more archetypes.
+    /// Use this to avoid reallocations of the archetype storage when many distinct archetypes are expected.
+    pub fn reserve_archetypes(&mut self, additional: usize) {
+        self.archetypes.reserve_archetypes(additional);
+    }
+
+    /// Reserves capacity for at least `additional` more entity handles.
+    /// Free slots of destroyed entities are reused first, so fewer new slots may be needed.
+    /// Together with [`Registry::try_reserve`] this avoids reallocations while creating many entities.
+    pub fn reserve_entities(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+    }
+
+    /// Rebuilds the internal archetype lookup index from the stored archetypes.
+    /// Lookups are kept consistent by every operation, so this is only needed to restore consistency
+    /// after bulk structural edits of the archetypes.
+    pub fn rebuild_index(&mut self) {
+        self.archetypes.rebuild_index();
+    }
+
+    /// Returns the amount of entity handles the registry can hold without reallocating.
+    pub fn entity_capacity(&self) -> usize {
+        self.entities.slot_capacity()
+    }
+
+    /// Creates the archetype storing exactly the components in [`G`] if it does not exist yet,
+    /// and reserves capacity for at least `capacity` entities in it, without creating any entity.
+    /// Use this while loading, so creating entities later on does not need to allocate.
+    /// Does nothing if the component group is invalid or an internal limit is exceeded.
+    /// Panics in case of allocation failure.
+    pub fn prewarm<G: ComponentGroup>(&mut self, capacity: u32) {
+        if let Some((_, archetype)) = self
+            .archetypes
+            .find_or_create_archetype(G::DESCRIPTOR.archetype())
+        {
+            archetype.reserve(capacity.saturating_sub(archetype.len()));
+        }
+    }
+
+    /// Reserves capacity for at least the given amount of entities in each of the given archetypes,
+    /// like [`Registry::prewarm`]. Use this with the per-archetype entity counts of a save,
+    /// so loading it does not need to reallocate archetypes.
+    /// Archetypes cannot be created from their id alone, so all archetypes must exist already.
+    /// Returns Err with the first id for which no archetype exists, without reserving anything.
+    /// Counts are clamped to the maximum amount of entities per archetype.
+    /// Panics in case of allocation failure.
+    pub fn reserve_from_histogram(
+        &mut self,
+        counts: &[(ArchetypeId, u32)],
+    ) -> Result<(), ArchetypeId> {
+        if let Some(&(archetype_id, _)) = counts.iter().find(|(archetype_id, _)| {
+            self.archetypes
+                .find_archetype_by_id(*archetype_id)
+                .is_none()
+        }) {
+            return Err(archetype_id);
+        }
+        for &(archetype_id, count) in counts {
+            let archetype = self
+                .archetypes
+                .find_archetype_by_id_mut(archetype_id)
+                .unwrap();
+            archetype.reserve(count.saturating_sub(archetype.len()));
+        }
+        Ok(())
+    }
+
+    /// Reserves capacity for at least `additional` more entities with exactly the components in [`G`].
+    /// Creates the archetype if it does not exist yet.
+    /// Returns Err instead of panicking if memory could not be allocated, leaving the capacity unchanged.
+    /// An archetype without any capacity reserves at least its initial capacity.
+    /// Also returns Err if the archetype could not be created, as an internal limit is exceeded.
+    pub fn try_reserve<G: ComponentGroup>(&mut self, additional: u32) -> Result<(), ReserveError> {
+        let (_, archetype) = self
+            .archetypes
+            .find_or_create_unallocated_archetype(G::DESCRIPTOR.archetype())
+            .ok_or(ReserveError::LimitExceeded)?;
+        let additional = match archetype.capacity() {
+            0 => core::cmp::max(additional, archetype.initial_capacity()),
+            _ => additional,
+        };
+        Ok(archetype.try_reserve(additional)?)
+    }
+
+    /// Sets the backend storing the component columns of the archetype storing exactly the components in [`G`].
+    /// Only affects the archetype if it is created afterwards.
+    pub fn set_column_backend<G: ComponentGroup>(&mut self, backend: &'static dyn ColumnBackend) {
+        self.archetypes
+            .set_column_backend(G::DESCRIPTOR.archetype().archetype_id(), backend);
+    }
+
+    /// Makes the archetype storing exactly the components in [`G`] acquire its column memory from the pool.
+    /// Memory is returned to the pool when the archetype grows or is dropped.
+    /// Only affects the archetype if it is created afterwards.
+    #[cfg(feature = "pool")]
+    pub fn replace_archetype_storage<G: ComponentGroup, P: StoragePool>(
+        &mut self,
+        pool: &'static P,
+    ) {
+        self.set_column_backend::<G>(pool);
+    }
+
+    /// Releases memory of the entity registry which is no longer in use.
+    /// Handles of existing entities stay valid, handles of destroyed entities stay invalid.
+    pub fn shrink_entity_registry(&mut self) {
+        self.entities.shrink_to_fit();
+    }
+
+    /// Sets the initial capacity of the archetype storing exactly the components in [`G`].
+    /// Only affects the archetype if it is created afterwards.
+    /// Tiny archetypes waste less memory with a small capacity, huge ones reallocate less with a large capacity.
+    pub fn set_initial_capacity<G: ComponentGroup>(&mut self, capacity: u32) {
+        self.archetypes
+            .set_initial_capacity(G::DESCRIPTOR.archetype().archetype_id(), capacity);
+    }
+
+    /// Registers the component type [`C`] with the registry.
+    /// Returns Err if a different type has already been registered using the same [`ComponentTypeId`].
+    /// Registering the same type multiple times is allowed.
+    /// Registration is optional, it allows detecting component type id collisions up front.
+    pub fn register_component<C: Component>(&mut self) -> Result<(), ComponentConflict> {
+        let (type_id, name) = *self
+            .registered_components
+            .entry(C::ID)
+            .or_insert((TypeId::of::<C>(), type_name::<C>()));
+        if type_id != TypeId::of::<C>() {
+            return Err(ComponentConflict {
+                id: C::ID,
+                registered: name,
+                conflicting: type_name::<C>(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the sorted component type ids of all components used by any archetype in the registry.
+    /// Each component type id is present only once.
+    pub fn known_component_ids(&self) -> Vec<ComponentTypeId> {
+        let mut ids = self
+            .archetypes
+            .iter_archetypes()
+            .flat_map(|archetype| archetype.descriptor().components().iter())
+            .map(|component| component.component_type_id())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns true if any archetype in the registry contains component [`C`].
+    /// Archetypes without entities are included.
+    pub fn component_present_in_any<C: Component>(&self) -> bool {
+        self.archetypes
+            .iter_archetypes()
+            .any(|archetype| archetype.descriptor().has_component::<C>())
+    }
+
+    /// Returns the sorted component type ids of every archetype in the registry.
+    /// Useful for finding archetypes which only differ by a few components.
+    pub fn archetype_shapes(&self) -> Vec<Vec<ComponentTypeId>> {
+        self.archetypes
+            .iter_archetypes()
+            .map(|archetype| {
+                archetype
+                    .descriptor()
+                    .components()
+                    .iter()
+                    .map(|component| component.component_type_id())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the stable id of the archetype with the given id, if it exists.
+    /// Unlike archetype indices, stable ids are never reused and stay valid for the lifetime of the registry.
+    pub fn stable_archetype_id(&self, archetype_id: ArchetypeId) -> Option<StableArchetypeId> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)
+            .map(|archetype| archetype.stable_id())
+    }
+
+    /// Returns the id of the archetype with the given stable id, if it exists.
+    pub fn archetype_id_of_stable(&self, stable_id: StableArchetypeId) -> Option<ArchetypeId> {
+        self.archetypes
+            .find_archetype_by_stable_id(stable_id)
+            .map(|archetype| archetype.descriptor().archetype_id())
+    }
+
+    /// Returns the amount of entities stored in the archetype with the given id.
+    /// Returns None if no such archetype exists.
+    pub fn len_of_archetype(&self, archetype_id: ArchetypeId) -> Option<u32> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)
+            .map(|archetype| archetype.len())
+    }
+
+    /// Returns the amount of entities the archetype with the given id can store without reallocating.
+    /// Returns None if no such archetype exists.
+    pub fn archetype_capacity(&self, archetype_id: ArchetypeId) -> Option<u32> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)
+            .map(|archetype| archetype.capacity())
+    }
+
+    /// Returns the amount of entities divided by the capacity over all archetypes.
+    /// Returns 1.0 if no capacity is allocated at all.
+    /// A low occupancy indicates a lot of allocated memory is unused.
+    pub fn occupancy(&self) -> f32 {
+        let (len, capacity) =
+            self.archetypes
+                .iter_archetypes()
+                .fold((0u64, 0u64), |(len, capacity), archetype| {
+                    (
+                        len + archetype.len() as u64,
+                        capacity + archetype.capacity() as u64,
+                    )
+                });
+        occupancy_of(len, capacity)
+    }
+
+    /// Returns the amount of entities divided by the capacity for every archetype.
+    /// Returns 1.0 for archetypes without allocated capacity.
+    pub fn archetype_occupancy(&self) -> Vec<(ArchetypeId, f32)> {
+        self.archetypes
+            .iter_archetypes()
+            .map(|archetype| {
+                (
+                    archetype.descriptor().archetype_id(),
+                    occupancy_of(archetype.len() as u64, archetype.capacity() as u64),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the amount of bytes occupied by instances of component [`C`] across all archetypes.
+    /// Only counts stored components, unused capacity is not included.
+    pub fn total_bytes_for<C: Component>(&self) -> usize {
+        self.archetypes
+            .iter_archetypes_matching::<C>()
+            .map(|archetype| archetype.len() as usize * core::mem::size_of::<C>())
+            .sum()
+    }
+
+    /// Returns a report describing the memory layout of every archetype in the registry.
+    pub fn layout_report(&self) -> Vec<ArchetypeLayout> {
+        self.archetypes
+            .iter_archetypes()
+            .map(|archetype| {
+                let capacity = archetype.capacity() as usize;
+                let components = archetype
+                    .descriptor()
+                    .components()
+                    .iter()
+                    .map(|component| ComponentLayout {
+                        component_type_id: component.component_type_id(),
+                        size: component.size(),
+                        align: component.align(),
+                        column_align: component.column_align(),
+                        allocated_bytes: component.size() as usize * capacity,
+                    })
+                    .collect::<Vec<_>>();
+                let allocated_bytes = components
+                    .iter()
+                    .map(|component| component.allocated_bytes)
+                    .sum::<usize>()
+                    + core::mem::size_of::<Entity>() * capacity;
+                ArchetypeLayout {
+                    archetype_id: archetype.descriptor().archetype_id(),
+                    entity_count: archetype.len(),
+                    capacity: archetype.capacity(),
+                    components,
+                    allocated_bytes,
+                }
+            })
+            .collect()
+    }
+
+    /// Calls the closure for every archetype with its id, its entities and its component columns.
+    /// Does not allocate, which makes it suitable for streaming the registry's contents to a writer.
+    pub fn for_each_archetype_raw<F: FnMut(ArchetypeId, &[Entity], &[RawColumn])>(&self, mut f: F) {
+        for archetype in self.archetypes.iter_archetypes() {
+            let mut columns = [RawColumn {
+                type_id: ComponentTypeId::INVALID,
+                ptr: core::ptr::null(),
+                len: 0,
+                stride: 0,
+            }; MAX_COMPONENTS_PER_ENTITY];
+            let mut len = 0;
+            for (slot, column) in columns.iter_mut().zip(archetype.raw_columns()) {
+                *slot = column;
+                len += 1;
+            }
+            f(
+                archetype.descriptor().archetype_id(),
+                archetype.entities(),
+                &columns[..len],
+            );
+        }
+    }
+
+    /// Returns the entities stored in the archetype with the given id, in the order they are stored in.
+    /// Returns None if no such archetype exists.
+    pub fn entities_of_archetype(&self, archetype_id: ArchetypeId) -> Option<&[Entity]> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)
+            .map(|archetype| archetype.entities())
+    }
+
+    /// Returns the amount of valid entities and free slots in the entity registry.
+    /// In debug builds, asserts that every slot is either valid or free,
+    /// and that every valid entity is stored at the row of its archetype it points to.
+    pub fn audit_entities(&self) -> EntityAudit {
+        let audit = EntityAudit {
+            live: self.entities.iter().count(),
+            free: self.entities.free_slot_count(),
+            total_slots: self.entities.slot_count(),
+        };
+        debug_assert_eq!(
+            audit.live + audit.free,
+            audit.total_slots,
+            "Entity slots are neither valid nor free."
+        );
+        #[cfg(debug_assertions)]
+        for entity in self.entities.iter() {
+            let entry = self.entities.entity_entry(entity).unwrap();
+            let archetype = &self.archetypes[entry.archetype_index()];
+            debug_assert_eq!(
+                archetype
+                    .entities()
+                    .get(entry.index_in_archetype().value() as usize),
+                Some(&entity),
+                "Entity {:?} is not stored at the row it points to.",
+                entity
+            );
+        }
+        audit
+    }
+
+    /// Panics if the internal bookkeeping of the registry is inconsistent.
+    /// Checks that every valid entity points to a row in an archetype which points back to it,
+    /// and that archetypes do not store any other entities.
+    /// Intended for tests and fuzzing, this visits every entity.
+    pub fn check_invariants(&self) {
+        self.entities.check_invariants();
+        let mut entity_count = 0usize;
+        for entity in self.entities.iter() {
+            let entry = self.entities.entity_entry(entity).unwrap();
+            let archetype = &self.archetypes[entry.archetype_index()];
+            let index = entry.index_in_archetype().value();
+            assert!(
+                index < archetype.len(),
+                "Entity {:?} points past the end of its archetype.",
+                entity
+            );
+            assert_eq!(
+                archetype.entities()[index as usize],
+                entity,
+                "Entity {:?} is not stored at the row it points to.",
+                entity
+            );
+            entity_count += 1;
+        }
+        let stored_count = self
+            .archetypes
+            .iter_archetypes()
+            .map(|archetype| {
+                assert!(archetype.len() <= archetype.capacity());
+                archetype.len() as usize
+            })
+            .sum::<usize>();
+        assert_eq!(
+            entity_count, stored_count,
+            "Archetypes store entities which are not valid."
+        );
+    }
+
+    /// Returns component [`C`] stored at the given location, as returned by [`Registry::entity_location`].
+    /// Avoids resolving the entity again, but the location is invalidated by structural changes.
+    /// A stale location may refer to a different entity, or return None.
+    /// Returns None if the archetype does not exist, the index is out of bounds or [`C`] is not present.
+    pub fn component_at<C: Component>(&self, location: (ArchetypeId, u32)) -> Option<&C> {
+        let (archetype_id, index) = location;
+        let archetype = self.archetypes.find_archetype_by_id(archetype_id)?;
+        archetype.column::<C>()?.get(index as usize)
+    }
+
+    /// Returns the amount of entities which have component [`C`], regardless of their other components.
+    /// Only visits archetypes, individual entities are not iterated.
+    pub fn entity_count_with_component<C: Component>(&self) -> u32 {
+        self.archetypes
+            .iter_archetypes_matching::<C>()
+            .map(|archetype| archetype.len())
+            .sum()
+    }
+
+    /// Returns an iterator over the ids of all archetypes containing component [`C`], including empty ones.
+    pub fn archetypes_with<C: Component>(&self) -> impl Iterator<Item = ArchetypeId> + '_ {
+        self.archetypes
+            .iter_archetypes_matching::<C>()
+            .map(|archetype| archetype.descriptor().archetype_id())
+    }
+
+    /// Returns for each pair of component types stored together the amount of entities having both,
+    /// as (first type, second type, entities) with the first type id smaller than the second.
+    /// Sorted by component type ids, pairs only present in empty archetypes are omitted.
+    pub fn co_occurrence(&self) -> Vec<(ComponentTypeId, ComponentTypeId, u64)> {
+        let mut counts: BTreeMap<(ComponentTypeId, ComponentTypeId), u64> = BTreeMap::new();
+        for archetype in self.archetypes.iter_archetypes().filter(|a| a.len() > 0) {
+            let components = archetype.descriptor().components();
+            for (i, first) in components.iter().enumerate() {
+                for second in &components[i + 1..] {
+                    let key = (first.component_type_id(), second.component_type_id());
+                    *counts.entry(key).or_insert(0) += archetype.len() as u64;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .map(|((first, second), count)| (first, second, count))
+            .collect()
+    }
+
+    /// Returns the column of component [`C`] in the archetype with the given id.
+    /// Returns None if the archetype does not exist or does not contain [`C`].
+    pub fn column_of<C: Component>(&self, archetype_id: ArchetypeId) -> Option<&[C]> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)?
+            .column::<C>()
+    }
+
+    /// Returns whether the archetype with the given id currently stores no entities.
+    /// Returns None if the archetype does not exist.
+    pub fn is_archetype_empty(&self, archetype_id: ArchetypeId) -> Option<bool> {
+        self.archetypes
+            .find_archetype_by_id(archetype_id)
+            .map(|archetype| archetype.len() == 0)
+    }
+
+    /// Returns all live entities sorted by archetype id, then by entity index.
+    /// Unlike archetype and row order, this order does not depend on the order
+    /// in which entities were created, moved or destroyed.
+    /// Useful for serializing the registry deterministically.
+    pub fn canonical_entity_order(&self) -> Vec<Entity> {
+        let mut entities = self
+            .archetypes
+            .iter_archetypes()
+            .flat_map(|archetype| {
+                let archetype_id = archetype.descriptor().archetype_id();
+                archetype
+                    .entities()
+                    .iter()
+                    .map(move |entity| (archetype_id, entity.index(), *entity))
+            })
+            .collect::<Vec<_>>();
+        entities.sort_unstable_by_key(|&(archetype_id, index, _)| (archetype_id, index));
+        entities.into_iter().map(|(_, _, entity)| entity).collect()
+    }
+
+    /// Replaces the entire contents of the registry by the contents of [`other`].
+    /// All current entities are dropped and the current archetypes are deallocated.
+    /// Unlike [`Registry::clone_from`], no archetypes of the current registry are reused.
+    /// Handles of the replaced contents are invalid afterwards, including handles of destroyed entities.
+    /// To achieve this, the entities of [`other`] keep their index but get a higher version,
+    /// so handles obtained from [`other`] are invalid as well. [`Registry::iter_entities`]
+    /// or [`Registry::entity_generation`] can be used to obtain the new handles.
+    pub fn replace_with(&mut self, mut other: Registry) {
+        other.entities.offset_versions_past(&self.entities);
+        for archetype in other.archetypes.iter_archetypes_mut() {
+            for entity in archetype.entities_mut() {
+                let version = other.entities.slot_version(entity.index()).unwrap();
+                entity.set_version(version);
+            }
+        }
+        *self = other;
+    }
+
+    /// Replaces the contents of the registry by clones of all entities and components in [`source`].
+    /// Reuses the memory of archetypes which exist in both registries and of the entity handles,
+    /// which is faster than cloning into a new registry when restoring similar states repeatedly.
+    /// Entities currently in the registry are dropped, entity handles of [`source`] are valid afterwards.
+    /// Returns Err with the first component which has no [`Component::CLONE_HANDLER`],
+    /// leaving the registry unchanged. Only components of entities in [`source`] need a clone handler.
+    /// If a clone handler panics, the registry is left without entities.
+    pub fn clone_from(&mut self, source: &Registry) -> Result<(), ComponentTypeId> {
+        let missing_clone_handler = source
+            .archetypes
+            .iter_archetypes()
+            .filter(|archetype| archetype.len() > 0)
+            .flat_map(|archetype| archetype.descriptor().components())
+            .find(|component| component.fns.clone_handler.is_none());
+        if let Some(component) = missing_clone_handler {
+            return Err(component.component_type_id());
+        }
+        self.entities.clear();
+        unsafe { self.archetypes.clone_from(&source.archetypes) };
+        self.entities.clone_from(&source.entities);
+        self.registered_components
+            .clone_from(&source.registered_components);
+        self.change_tick = source.change_tick;
+        #[cfg(feature = "stats")]
+        self.transition_counts.clone_from(&source.transition_counts);
+        Ok(())
+    }
+
+    /// Returns the current generation of the entity slot with the given index, regardless of any handle.
+    /// A handle with this index is only valid if its [`Entity::version`] equals the generation
+    /// and the slot is in use.
+    /// Returns None if the index is out of range.
+    pub fn entity_generation(&self, index: u32) -> Option<u32> {
+        self.entities.slot_version(index).map(u32::from)
+    }
+
+    /// Returns the id of the archetype the entity is stored in and its index within that archetype.
+    /// Returns None if the entity is invalid.
+    /// The location changes whenever components are added to or removed from the entity,
+    /// or when another entity in the same archetype is removed.
+    pub fn entity_location(&self, entity: Entity) -> Option<(ArchetypeId, u32)> {
+        let entry = self.entities.entity_entry(entity)?;
+        let archetype = unsafe { self.archetypes.get_unchecked(entry.archetype_index()) };
+        Some((
+            archetype.descriptor().archetype_id(),
+            entry.index_in_archetype().value(),
+        ))
+    }
+}
+
+fn occupancy_of(len: u64, capacity: u64) -> f32 {
+    if capacity == 0 {
+        return 1.0;
+    }
+    (len as f64 / capacity as f64) as f32
+}
+
+impl<G: ComponentGroup> FromIterator<G> for Registry {
+    /// Creates a registry containing an entity for every component group in the iterator.
+    /// Component groups for which no entity could be created are dropped.
+    fn from_iter<I: IntoIterator<Item = G>>(iter: I) -> Self {
+        let mut registry = Registry::default();
+        registry.extend(iter);
+        registry
+    }
+}
+
+impl<G: ComponentGroup> Extend<G> for Registry {
+    /// Creates an entity for every component group in the iterator, like [`Registry::spawn_contiguous`].
+    /// Capacity is reserved up front based on the size hint of the iterator.
+    /// Component groups for which no entity could be created are dropped.
+    fn extend<I: IntoIterator<Item = G>>(&mut self, iter: I) {
+        self.spawn_contiguous(iter);
+    }
 }
@@ -0,0 +1,29 @@
+use core::fmt;
+
+/// Error returned by [`crate::Registry::remove_component`] if the component could not be removed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemoveComponentError {
+    /// The entity handle is invalid.
+    InvalidEntity,
+    /// The entity does not have the component.
+    MissingComponent,
+    /// The destination archetype could not be created or is full, as an internal limit is exceeded.
+    LimitExceeded,
+}
+
+impl fmt::Display for RemoveComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoveComponentError::InvalidEntity => write!(f, "the entity is invalid"),
+            RemoveComponentError::MissingComponent => {
+                write!(f, "the entity does not have the component")
+            }
+            RemoveComponentError::LimitExceeded => {
+                write!(
+                    f,
+                    "the destination archetype could not be created or is full"
+                )
+            }
+        }
+    }
+}
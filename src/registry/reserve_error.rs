@@ -0,0 +1,26 @@
+use crate::archetype::AllocError;
+use core::fmt;
+
+/// Error returned by [`crate::Registry::try_reserve`] if the capacity could not be reserved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReserveError {
+    /// The archetype could not be created, as an internal limit is exceeded.
+    LimitExceeded,
+    /// Memory could not be allocated.
+    AllocError(AllocError),
+}
+
+impl From<AllocError> for ReserveError {
+    fn from(error: AllocError) -> Self {
+        ReserveError::AllocError(error)
+    }
+}
+
+impl fmt::Display for ReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReserveError::LimitExceeded => write!(f, "the archetype could not be created"),
+            ReserveError::AllocError(error) => error.fmt(f),
+        }
+    }
+}
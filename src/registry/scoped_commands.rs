@@ -0,0 +1,96 @@
+use crate::descriptors::component_group::ComponentGroup;
+use crate::{Component, Entity, Registry};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ops::Deref;
+
+type Command = Box<dyn FnOnce(&mut Registry)>;
+
+/// Records structural changes and applies them to the registry when dropped or flushed.
+/// Dereferences to the registry, so it can be read while changes are being recorded.
+/// Changes are applied in the order they were recorded, failing changes are ignored.
+/// Recorded changes are also applied when the guard is dropped during a panic.
+/// Created by [`Registry::scoped_commands`].
+pub struct ScopedCommands<'r> {
+    registry: &'r mut Registry,
+    commands: RefCell<Vec<Command>>,
+}
+
+impl<'r> ScopedCommands<'r> {
+    pub(crate) fn new(registry: &'r mut Registry) -> Self {
+        Self {
+            registry,
+            commands: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Records the creation of an entity using the provided components.
+    pub fn create_entity<G: ComponentGroup + 'static>(&self, components: G) {
+        self.record(move |registry| {
+            let _ = registry.create_entity(components);
+        });
+    }
+
+    /// Records the destruction of the entity.
+    pub fn destroy_entity(&self, entity: Entity) {
+        self.record(move |registry| {
+            registry.destroy_entity(entity);
+        });
+    }
+
+    /// Records adding the component to the entity.
+    pub fn add_component<C: Component>(&self, entity: Entity, component: C) {
+        self.record(move |registry| {
+            let _ = registry.add_component(entity, component);
+        });
+    }
+
+    /// Records removing component [`C`] from the entity, the removed component is dropped.
+    pub fn remove_component<C: Component>(&self, entity: Entity) {
+        self.record(move |registry| {
+            let _ = registry.remove_component::<C>(entity);
+        });
+    }
+
+    /// Returns the amount of recorded changes which have not been applied yet.
+    pub fn len(&self) -> usize {
+        self.commands.borrow().len()
+    }
+
+    /// Returns true if there are no recorded changes which have not been applied yet.
+    pub fn is_empty(&self) -> bool {
+        self.commands.borrow().is_empty()
+    }
+
+    /// Applies all recorded changes to the registry.
+    pub fn flush(&mut self) {
+        let commands = core::mem::take(self.commands.get_mut());
+        for command in commands {
+            command(self.registry);
+        }
+    }
+
+    /// Discards all recorded changes without applying them.
+    pub fn discard(&mut self) {
+        self.commands.get_mut().clear();
+    }
+
+    fn record<F: FnOnce(&mut Registry) + 'static>(&self, command: F) {
+        self.commands.borrow_mut().push(Box::new(command));
+    }
+}
+
+impl<'r> Deref for ScopedCommands<'r> {
+    type Target = Registry;
+
+    fn deref(&self) -> &Self::Target {
+        self.registry
+    }
+}
+
+impl<'r> Drop for ScopedCommands<'r> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
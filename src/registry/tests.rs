@@ -3,6 +3,7 @@ use crate::test_components::*;
 #[cfg(test)]
 use crate::*;
 use alloc::vec::Vec;
+use core::ops::Range;
 
 #[test]
 fn registry_test_get_component() {
@@ -191,3 +192,2196 @@ fn test_registry() {
         }
     }
 }
+
+#[test]
+fn registry_test_known_component_ids() {
+    let mut registry = Registry::default();
+    assert!(registry.known_component_ids().is_empty());
+    registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    registry
+        .create_entity((C::default(), B::default()))
+        .unwrap();
+    registry.create_entity(A::default()).unwrap();
+    assert_eq!(registry.known_component_ids(), [A::ID, B::ID, C::ID]);
+}
+
+#[test]
+fn registry_test_archetype_shapes() {
+    let mut registry = Registry::default();
+    assert!(registry.archetype_shapes().is_empty());
+    registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    registry
+        .create_entity((C::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let mut shapes = registry.archetype_shapes();
+    shapes.sort();
+    assert_eq!(shapes, [[A::ID, B::ID], [B::ID, C::ID]]);
+}
+
+#[test]
+fn registry_test_len_of_archetype() {
+    let mut registry = Registry::default();
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), None);
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let entity = registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    registry.create_entity(C::default()).unwrap();
+    assert_eq!(registry.len_of_archetype(id), Some(2));
+    assert_eq!(
+        registry.len_of_archetype(<C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id()),
+        Some(1)
+    );
+    registry.destroy_entity(entity);
+    assert_eq!(registry.len_of_archetype(id), Some(1));
+}
+
+#[test]
+fn registry_test_for_each_with_entity_mut() {
+    let mut registry = Registry::default();
+    let mut entities = Vec::new();
+    for i in 0..4 {
+        entities.push(
+            registry
+                .create_entity((A { _data: i }, B::default()))
+                .unwrap(),
+        );
+        entities.push(
+            registry
+                .create_entity((A { _data: i }, B::default(), C::default()))
+                .unwrap(),
+        );
+    }
+    registry.create_entity(C::default()).unwrap();
+
+    let mut visited = Vec::new();
+    registry.for_each_with_entity_mut::<(B, A), _>(|entity, (b, a)| {
+        visited.push(entity);
+        b._data = a._data * 10;
+    });
+    visited.sort_by_key(|e| e.index());
+    entities.sort_by_key(|e| e.index());
+    assert_eq!(visited, entities);
+    for entity in entities {
+        let (a, b) = registry.get_components::<(A, B)>(entity).unwrap();
+        assert_eq!(b._data, a._data * 10);
+    }
+}
+
+#[test]
+fn registry_test_spawn_batch_same() {
+    let mut registry = Registry::default();
+    let entities = registry.spawn_batch_same((B { _data: 5 }, A { _data: 6 }), 1000);
+    assert_eq!(entities.len(), 1000);
+    for entity in &entities {
+        assert_eq!(
+            registry.get_components::<(A, B)>(*entity),
+            Some((&A { _data: 6 }, &B { _data: 5 }))
+        );
+    }
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), Some(1000));
+    assert!(registry.spawn_batch_same(C::default(), 0).is_empty());
+}
+
+#[test]
+fn registry_test_component_ptr_mut() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let ptr = registry.component_ptr_mut::<B>(entity).unwrap();
+    unsafe { (*ptr)._data = 3 };
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 3 }));
+    assert!(registry.component_ptr_mut::<C>(entity).is_none());
+    assert!(registry.destroy_entity(entity));
+    assert!(registry.component_ptr_mut::<A>(entity).is_none());
+}
+
+#[test]
+fn registry_test_query_segmented() {
+    let mut registry = Registry::default();
+    for i in 0..3 {
+        registry
+            .create_entity((A { _data: i }, B { _data: i }))
+            .unwrap();
+    }
+    for i in 0..2 {
+        registry
+            .create_entity((A { _data: 10 + i }, B { _data: i }, C { _data: i }))
+            .unwrap();
+    }
+    registry.create_entity(C::default()).unwrap();
+
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let abc = <(A, B, C) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let mut segments = registry
+        .query_segmented::<(A, B)>()
+        .map(|(id, (a, b))| {
+            assert_eq!(a.len(), b.len());
+            (id, a.iter().map(|a| a._data).sum::<usize>(), a.len())
+        })
+        .collect::<Vec<_>>();
+    segments.sort_by_key(|(_, _, len)| *len);
+    assert_eq!(segments, [(abc, 21, 2), (ab, 3, 3)]);
+}
+
+#[test]
+fn registry_test_register_component() {
+    struct Colliding;
+    impl Component for Colliding {
+        const NAME: &'static str = "Colliding";
+        const ID: ComponentTypeId = A::ID;
+    }
+
+    let mut registry = Registry::default();
+    assert_eq!(registry.register_component::<A>(), Ok(()));
+    assert_eq!(registry.register_component::<B>(), Ok(()));
+    assert_eq!(registry.register_component::<A>(), Ok(()));
+    let conflict = registry.register_component::<Colliding>().unwrap_err();
+    assert_eq!(conflict.id, A::ID);
+    assert_eq!(conflict.registered, core::any::type_name::<A>());
+    assert_eq!(conflict.conflicting, core::any::type_name::<Colliding>());
+}
+
+#[test]
+fn registry_test_entity_location() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let second = registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    let third = registry.create_entity(A::default()).unwrap();
+
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let a = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    assert_eq!(registry.entity_location(first), Some((ab, 0)));
+    assert_eq!(registry.entity_location(second), Some((ab, 1)));
+    assert_eq!(registry.entity_location(third), Some((a, 0)));
+
+    assert!(registry.destroy_entity(first));
+    assert_eq!(registry.entity_location(first), None);
+    assert_eq!(registry.entity_location(second), Some((ab, 0)));
+}
+
+#[test]
+fn registry_test_from_iter() {
+    let registry = (0..10)
+        .map(|i| (A { _data: i }, B { _data: 2 * i }))
+        .collect::<Registry>();
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), Some(10));
+    let sum = registry
+        .iter_components_matching::<(A, B)>()
+        .flat_map(|(a, b)| a.iter().zip(b.iter()))
+        .map(|(a, b)| a._data + b._data)
+        .sum::<usize>();
+    assert_eq!(sum, 135);
+}
+
+#[test]
+fn registry_test_get_component_or_insert_with() {
+    let mut registry = Registry::default();
+    let other = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let entity = registry
+        .create_entity((A { _data: 3 }, B { _data: 4 }))
+        .unwrap();
+
+    let c = registry
+        .get_component_or_insert_with(entity, || C { _data: 5 })
+        .unwrap();
+    assert_eq!(c._data, 5);
+    c._data = 6;
+    assert_eq!(
+        registry.get_components::<(A, B, C)>(entity),
+        Some((&A { _data: 3 }, &B { _data: 4 }, &C { _data: 6 }))
+    );
+    assert_eq!(registry.get_component::<A>(other), Some(&A { _data: 1 }));
+
+    let a = registry
+        .get_component_or_insert_with(entity, || -> A { unreachable!() })
+        .unwrap();
+    assert_eq!(a._data, 3);
+
+    assert!(registry.destroy_entity(entity));
+    assert!(registry
+        .get_component_or_insert_with(entity, || -> C { unreachable!() })
+        .is_none());
+}
+
+#[test]
+fn registry_test_entities_of_archetype() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let other = registry.create_entity(A::default()).unwrap();
+    let second = registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    let third = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let a = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let c = <C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    assert_eq!(
+        registry.entities_of_archetype(ab),
+        Some(&[first, second, third][..])
+    );
+    assert_eq!(registry.entities_of_archetype(a), Some(&[other][..]));
+    assert_eq!(registry.entities_of_archetype(c), None);
+
+    assert!(registry.destroy_entity(first));
+    assert_eq!(
+        registry.entities_of_archetype(ab),
+        Some(&[third, second][..])
+    );
+}
+
+#[test]
+fn registry_test_shrink_entity_registry() {
+    let mut registry = Registry::default();
+    let entities = (0..100)
+        .map(|i| registry.create_entity(A { _data: i }).unwrap())
+        .collect::<Vec<_>>();
+    for entity in &entities[50..] {
+        assert!(registry.destroy_entity(*entity));
+    }
+    registry.shrink_entity_registry();
+    for (i, entity) in entities[..50].iter().enumerate() {
+        assert_eq!(registry.get_component::<A>(*entity), Some(&A { _data: i }));
+    }
+    assert_eq!(registry.iter_entities().count(), 50);
+    let entity = registry.create_entity(A { _data: 100 }).unwrap();
+    assert_eq!(registry.get_component::<A>(entity), Some(&A { _data: 100 }));
+}
+
+#[test]
+fn registry_test_shrink_entity_registry_stale_handles() {
+    let mut registry = Registry::default();
+    let live = registry.create_entity(A { _data: 0 }).unwrap();
+    let stale = registry.create_entity(A { _data: 1 }).unwrap();
+    assert!(registry.destroy_entity(stale));
+    registry.shrink_entity_registry();
+
+    let respawned = registry.create_entity(A { _data: 2 }).unwrap();
+    assert_eq!(respawned.index(), stale.index());
+    assert_eq!(registry.entity_location(stale), None);
+    assert_eq!(registry.get_component::<A>(stale), None);
+    assert!(!registry.destroy_entity(stale));
+    assert_eq!(
+        registry.get_component::<A>(respawned),
+        Some(&A { _data: 2 })
+    );
+    assert_eq!(registry.get_component::<A>(live), Some(&A { _data: 0 }));
+}
+
+#[test]
+fn registry_test_try_query() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A { _data: 1 }, B::default()))
+        .unwrap();
+    registry
+        .create_entity((A { _data: 2 }, C::default()))
+        .unwrap();
+    let Ok(query) = registry.try_query::<A>();
+    let sum = query.flat_map(|a| a.iter()).map(|a| a._data).sum::<usize>();
+    assert_eq!(sum, 3);
+    assert_eq!(registry.try_query::<(A, B, C)>().unwrap().count(), 0);
+}
+
+#[test]
+fn registry_test_query_archetypes_changed_since() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), C::default()))
+        .unwrap();
+    registry.create_entity(A::default()).unwrap();
+
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let ac = <(A, C) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let a = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let changed = |registry: &Registry, tick| {
+        let mut ids = registry
+            .query_archetypes_changed_since::<A>(tick)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    let mut all = [ab, ac, a];
+    all.sort();
+    assert_eq!(changed(&registry, Tick::ZERO), all);
+
+    let tick = registry.change_tick();
+    assert!(changed(&registry, tick).is_empty());
+    let _ = registry.iter_components_matching::<A>().count();
+    assert!(changed(&registry, tick).is_empty());
+
+    registry.get_component_mut::<B>(entity).unwrap()._data = 1;
+    assert_eq!(changed(&registry, tick), [ab]);
+
+    let tick = registry.change_tick();
+    let _ = registry.iter_components_matching_mut::<C>().count();
+    assert_eq!(changed(&registry, tick), [ac]);
+
+    let tick = registry.change_tick();
+    registry.add_component(entity, C::default()).unwrap();
+    let (abc, _) = registry.entity_location(entity).unwrap();
+    let mut expected = [ab, abc];
+    expected.sort();
+    assert_eq!(changed(&registry, tick), expected);
+
+    let tick = registry.change_tick();
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(changed(&registry, tick), [abc]);
+}
+
+#[test]
+fn registry_test_occupancy() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.occupancy(), 1.0);
+    assert!(registry.archetype_occupancy().is_empty());
+
+    registry.set_initial_capacity::<(A, B)>(4);
+    registry.set_initial_capacity::<C>(8);
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap();
+    }
+    registry.create_entity(C::default()).unwrap();
+
+    assert_eq!(registry.occupancy(), 4.0 / 12.0);
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let c = <C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let mut occupancy = registry.archetype_occupancy();
+    occupancy.sort_by_key(|(id, _)| *id);
+    let mut expected = [(ab, 0.75), (c, 0.125)];
+    expected.sort_by_key(|(id, _)| *id);
+    assert_eq!(occupancy, expected);
+}
+
+#[test]
+fn registry_test_remove_entity() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let second = registry
+        .create_entity((A { _data: 3 }, B { _data: 4 }))
+        .unwrap();
+
+    assert_eq!(registry.remove_entity::<A>(first), None);
+    assert_eq!(registry.remove_entity::<(A, B, C)>(first), None);
+    assert_eq!(registry.get_component::<A>(first), Some(&A { _data: 1 }));
+
+    assert_eq!(
+        registry.remove_entity::<(B, A)>(first),
+        Some((B { _data: 2 }, A { _data: 1 }))
+    );
+    assert!(!registry.has_component::<A>(first));
+    assert_eq!(
+        registry.get_components::<(A, B)>(second),
+        Some((&A { _data: 3 }, &B { _data: 4 }))
+    );
+}
+
+#[test]
+fn registry_test_apply_raw_component() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let bytes = 42usize.to_ne_bytes();
+    unsafe {
+        assert_eq!(registry.apply_raw_component(entity, B::ID, &bytes), Ok(()));
+        assert_eq!(
+            registry.apply_raw_component(entity, C::ID, &bytes),
+            Err(ApplyError::MissingComponent)
+        );
+        assert_eq!(
+            registry.apply_raw_component(entity, A::ID, &bytes[1..]),
+            Err(ApplyError::SizeMismatch {
+                expected: bytes.len(),
+                actual: bytes.len() - 1
+            })
+        );
+    }
+    assert_eq!(
+        registry.get_components::<(A, B)>(entity),
+        Some((&A { _data: 1 }, &B { _data: 42 }))
+    );
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(
+        unsafe { registry.apply_raw_component(entity, B::ID, &bytes) },
+        Err(ApplyError::InvalidEntity)
+    );
+}
+
+#[test]
+fn registry_test_count_filtered() {
+    let mut registry = Registry::default();
+    for _ in 0..5 {
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap();
+    }
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), B::default(), C::default()))
+            .unwrap();
+    }
+    registry.create_entity(C::default()).unwrap();
+
+    assert_eq!(registry.count_filtered::<A, _>(|_| true), 8);
+    assert_eq!(registry.count_filtered::<C, _>(|_| true), 4);
+    let without_c = |descriptor: &archetype_descriptor::ArchetypeDescriptor| {
+        descriptor.excludes_subset(<C as ComponentGroup>::DESCRIPTOR.archetype())
+    };
+    assert_eq!(registry.count_filtered::<(A, B), _>(without_c), 5);
+    assert_eq!(registry.count_filtered::<(A, C), _>(without_c), 0);
+}
+
+#[test]
+fn registry_test_layout_report() {
+    let mut registry = Registry::default();
+    assert!(registry.layout_report().is_empty());
+    registry.set_initial_capacity::<(A, B)>(4);
+    for _ in 0..3 {
+        registry
+            .create_entity((B::default(), A::default()))
+            .unwrap();
+    }
+
+    let report = registry.layout_report();
+    assert_eq!(report.len(), 1);
+    let layout = &report[0];
+    let descriptor = <(A, B) as ComponentGroup>::DESCRIPTOR.archetype();
+    assert_eq!(layout.archetype_id, descriptor.archetype_id());
+    assert_eq!(layout.entity_count, 3);
+    assert_eq!(layout.capacity, 4);
+    let size = core::mem::size_of::<usize>();
+    for (component, expected) in layout.components.iter().zip(descriptor.components()) {
+        assert_eq!(component.component_type_id, expected.component_type_id());
+        assert_eq!(component.size as usize, size);
+        assert_eq!(component.allocated_bytes, 4 * size);
+    }
+    assert_eq!(layout.components.len(), 2);
+    assert_eq!(
+        layout.allocated_bytes,
+        4 * (2 * size + core::mem::size_of::<Entity>())
+    );
+}
+
+#[test]
+fn registry_test_create_entity_returning_location() {
+    let mut registry = Registry::default();
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    for i in 0..3 {
+        let (entity, id, index) = registry
+            .create_entity_returning_location((B::default(), A { _data: i }))
+            .unwrap();
+        assert_eq!((id, index), (ab, i as u32));
+        assert_eq!(registry.entity_location(entity), Some((id, index)));
+    }
+}
+
+#[test]
+fn registry_test_extend() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A { _data: 0 }, B::default()))
+        .unwrap();
+    registry.extend((1..10).map(|i| (B::default(), A { _data: i })));
+    registry.extend(core::iter::empty::<(A, B)>());
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), Some(10));
+    let mut values = registry
+        .iter_components_matching::<A>()
+        .flat_map(|a| a.iter().map(|a| a._data))
+        .collect::<Vec<_>>();
+    values.sort_unstable();
+    assert!(values.into_iter().eq(0..10));
+    assert_eq!(registry.iter_entities().count(), 10);
+}
+
+#[test]
+fn registry_test_for_each_archetype_raw() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let second = registry
+        .create_entity((A { _data: 3 }, B { _data: 4 }))
+        .unwrap();
+    let third = registry.create_entity(C { _data: 5 }).unwrap();
+
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let c = <C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let mut visited = 0;
+    registry.for_each_archetype_raw(|id, entities, columns| {
+        visited += 1;
+        if id == ab {
+            assert_eq!(entities, &[first, second]);
+            assert_eq!(columns.len(), 2);
+            let a = columns
+                .iter()
+                .find(|column| column.type_id == A::ID)
+                .unwrap();
+            let bytes = unsafe { a.as_bytes() };
+            assert_eq!(bytes.len(), 2 * core::mem::size_of::<usize>());
+            assert_eq!(bytes[..core::mem::size_of::<usize>()], 1usize.to_ne_bytes());
+        } else {
+            assert_eq!(id, c);
+            assert_eq!(entities, &[third]);
+            assert_eq!(columns.len(), 1);
+            assert_eq!(columns[0].len, 1);
+        }
+    });
+    assert_eq!(visited, 2);
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn registry_test_replace_archetype_storage() {
+    use alloc::alloc::{alloc, dealloc, Layout};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingPool {
+        acquired: AtomicUsize,
+        released: AtomicUsize,
+    }
+
+    unsafe impl StoragePool for CountingPool {
+        unsafe fn acquire(&self, layout: Layout) -> *mut u8 {
+            self.acquired.fetch_add(1, Ordering::Relaxed);
+            alloc(layout)
+        }
+
+        unsafe fn release(&self, ptr: *mut u8, layout: Layout) {
+            self.released.fetch_add(1, Ordering::Relaxed);
+            dealloc(ptr, layout)
+        }
+    }
+
+    static POOL: CountingPool = CountingPool {
+        acquired: AtomicUsize::new(0),
+        released: AtomicUsize::new(0),
+    };
+
+    let mut registry = Registry::default();
+    registry.replace_archetype_storage::<(A, B), _>(&POOL);
+    registry.set_initial_capacity::<(A, B)>(2);
+    let entities = [
+        registry
+            .create_entity((A { _data: 1 }, B { _data: 2 }))
+            .unwrap(),
+        registry
+            .create_entity((A { _data: 3 }, B { _data: 4 }))
+            .unwrap(),
+    ];
+    assert_eq!(POOL.acquired.load(Ordering::Relaxed), 2);
+    let entity = registry
+        .create_entity((A { _data: 5 }, B { _data: 6 }))
+        .unwrap();
+    assert_eq!(POOL.acquired.load(Ordering::Relaxed), 4);
+    assert_eq!(POOL.released.load(Ordering::Relaxed), 2);
+    assert_eq!(
+        registry.get_component::<A>(entities[0]),
+        Some(&A { _data: 1 })
+    );
+    assert_eq!(
+        registry.get_component::<B>(entities[1]),
+        Some(&B { _data: 4 })
+    );
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 6 }));
+
+    // Other archetypes do not use the pool.
+    registry.create_entity(A::default()).unwrap();
+    assert_eq!(POOL.acquired.load(Ordering::Relaxed), 4);
+
+    drop(registry);
+    assert_eq!(POOL.released.load(Ordering::Relaxed), 4);
+}
+
+#[test]
+fn registry_test_iter_single() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A { _data: 1 }, B::default()))
+        .unwrap();
+    registry.create_entity(A { _data: 2 }).unwrap();
+    registry
+        .create_entity((C::default(), A { _data: 3 }))
+        .unwrap();
+    registry.create_entity(B::default()).unwrap();
+    let mut values = registry
+        .iter_single::<A>()
+        .flatten()
+        .map(|a| a._data)
+        .collect::<Vec<_>>();
+    values.sort_unstable();
+    assert_eq!(values, [1, 2, 3]);
+    assert_eq!(registry.iter_single::<C>().count(), 1);
+
+    registry
+        .iter_single_mut::<A>()
+        .flatten()
+        .for_each(|a| a._data *= 10);
+    let sum: usize = registry.iter_single::<A>().flatten().map(|a| a._data).sum();
+    assert_eq!(sum, 60);
+}
+
+#[test]
+fn registry_test_spawn_mut() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let (entity, b) = registry
+        .spawn_mut::<_, B>((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    b._data = 3;
+    assert_eq!(registry.get_component::<A>(entity), Some(&A { _data: 1 }));
+    assert_eq!(registry.get_component::<B>(entity), Some(&B { _data: 3 }));
+    assert_eq!(registry.iter_entities().count(), 2);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn registry_test_par_spawn_batch() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Index(usize);
+    impl Component for Index {
+        const NAME: &'static str = "Index";
+    }
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Square(usize);
+    impl Component for Square {
+        const NAME: &'static str = "Square";
+    }
+
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((Index(usize::MAX), Square(0)))
+        .unwrap();
+    let entities = registry.par_spawn_batch(10_000, |i| (Square(i * i), Index(i)));
+    assert_eq!(entities.len(), 10_000);
+    assert_eq!(
+        registry.get_component::<Index>(first),
+        Some(&Index(usize::MAX))
+    );
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(registry.get_component::<Index>(*entity), Some(&Index(i)));
+        assert_eq!(
+            registry.get_component::<Square>(*entity),
+            Some(&Square(i * i))
+        );
+    }
+    assert_eq!(registry.iter_entities().count(), 10_001);
+}
+
+#[test]
+fn registry_test_audit_entities() {
+    let mut registry = Registry::default();
+    let entities = [
+        registry.create_entity(A::default()).unwrap(),
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap(),
+        registry.create_entity(A::default()).unwrap(),
+        registry.create_entity(C::default()).unwrap(),
+    ];
+    registry.destroy_entity(entities[0]);
+    registry.destroy_entity(entities[3]);
+    assert_eq!(
+        registry.audit_entities(),
+        EntityAudit {
+            live: 2,
+            free: 2,
+            total_slots: 4
+        }
+    );
+    registry.create_entity(B::default()).unwrap();
+    assert_eq!(
+        registry.audit_entities(),
+        EntityAudit {
+            live: 3,
+            free: 1,
+            total_slots: 4
+        }
+    );
+}
+
+#[test]
+fn registry_test_spawn_dynamic() {
+    use crate::descriptors::component_descriptor::ComponentDescriptor;
+
+    struct Bundle {
+        descriptors: Vec<ComponentDescriptor>,
+        a: A,
+        b: Option<B>,
+    }
+
+    unsafe impl DynBundle for Bundle {
+        fn descriptors(&self) -> &[ComponentDescriptor] {
+            &self.descriptors
+        }
+
+        unsafe fn write_components(&self, destinations: &[*mut u8]) {
+            for (descriptor, destination) in self.descriptors.iter().zip(destinations) {
+                if descriptor.component_type_id() == <A as Component>::ID {
+                    core::ptr::write(*destination as *mut A, self.a.clone());
+                } else {
+                    core::ptr::write(*destination as *mut B, self.b.clone().unwrap());
+                }
+            }
+        }
+    }
+
+    let mut registry = Registry::default();
+    let existing = registry
+        .create_entity((B { _data: 0 }, A { _data: 0 }))
+        .unwrap();
+    let both = Bundle {
+        descriptors: alloc::vec![<A as Component>::DESCRIPTOR, <B as Component>::DESCRIPTOR],
+        a: A { _data: 1 },
+        b: Some(B { _data: 2 }),
+    };
+    let single = Bundle {
+        descriptors: alloc::vec![<A as Component>::DESCRIPTOR],
+        a: A { _data: 3 },
+        b: None,
+    };
+
+    let first = registry.spawn_dynamic(&both).unwrap();
+    let second = registry.spawn_dynamic(&single).unwrap();
+    assert_eq!(
+        registry.get_components::<(A, B)>(first),
+        Some((&A { _data: 1 }, &B { _data: 2 }))
+    );
+    assert_eq!(registry.get_component::<A>(second), Some(&A { _data: 3 }));
+    assert!(!registry.has_component::<B>(second));
+    assert_eq!(
+        registry.entity_location(first).unwrap().0,
+        registry.entity_location(existing).unwrap().0
+    );
+    assert_eq!(registry.audit_entities().live, 3);
+
+    let unsorted = Bundle {
+        descriptors: both.descriptors.iter().rev().cloned().collect(),
+        a: A::default(),
+        b: Some(B::default()),
+    };
+    assert_eq!(registry.spawn_dynamic(&unsorted), None);
+}
+
+#[test]
+fn registry_test_changed_since() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 1 }, B::default()))
+        .unwrap();
+    let second = registry
+        .create_entity((A { _data: 2 }, B::default()))
+        .unwrap();
+    let third = registry
+        .create_entity((A { _data: 3 }, C::default()))
+        .unwrap();
+    assert_eq!(registry.changed_since::<A>(Tick::ZERO).count(), 3);
+
+    let tick = registry.change_tick();
+    assert_eq!(registry.changed_since::<A>(tick).count(), 0);
+    registry.get_component_mut::<C>(third).unwrap()._data = 4;
+    let changed = registry.changed_since::<(C, A)>(tick).collect::<Vec<_>>();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].0, third);
+    assert_eq!(changed[0].1, (&C { _data: 4 }, &A { _data: 3 }));
+
+    let tick = registry.change_tick();
+    registry.get_component_mut::<B>(second).unwrap()._data = 5;
+    let mut changed = registry
+        .changed_since::<A>(tick)
+        .map(|(entity, a)| (entity.to_bits(), a._data))
+        .collect::<Vec<_>>();
+    changed.sort_unstable();
+    assert_eq!(changed, [(first.to_bits(), 1), (second.to_bits(), 2)]);
+}
+
+#[test]
+fn registry_test_total_bytes_for() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.total_bytes_for::<A>(), 0);
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), C::default()))
+        .unwrap();
+    registry.create_entity(A::default()).unwrap();
+    registry.create_entity(B::default()).unwrap();
+    assert_eq!(
+        registry.total_bytes_for::<A>(),
+        3 * core::mem::size_of::<A>()
+    );
+    assert_eq!(
+        registry.total_bytes_for::<B>(),
+        2 * core::mem::size_of::<B>()
+    );
+    assert_eq!(registry.total_bytes_for::<C>(), core::mem::size_of::<C>());
+}
+
+#[test]
+fn registry_test_migrate() {
+    let mut registry = Registry::default();
+    let only_a = registry.create_entity(A { _data: 1 }).unwrap();
+    let a_b = registry
+        .create_entity((A { _data: 2 }, B { _data: 20 }))
+        .unwrap();
+    let a_c = registry
+        .create_entity((A { _data: 3 }, C { _data: 30 }))
+        .unwrap();
+    let a_c_2 = registry
+        .create_entity((C { _data: 40 }, A { _data: 4 }))
+        .unwrap();
+    let only_b = registry.create_entity(B { _data: 50 }).unwrap();
+
+    registry.migrate::<A, C, _>(|a| C {
+        _data: a._data * 100,
+    });
+    assert_eq!(registry.total_bytes_for::<A>(), 0);
+    assert_eq!(registry.get_component::<C>(only_a), Some(&C { _data: 100 }));
+    assert_eq!(
+        registry.get_components::<(B, C)>(a_b),
+        Some((&B { _data: 20 }, &C { _data: 200 }))
+    );
+    assert_eq!(registry.get_component::<C>(a_c), Some(&C { _data: 300 }));
+    assert_eq!(registry.get_component::<C>(a_c_2), Some(&C { _data: 400 }));
+    assert!(!registry.has_component::<A>(a_c));
+    assert_eq!(registry.get_component::<B>(only_b), Some(&B { _data: 50 }));
+    assert_eq!(registry.audit_entities().live, 5);
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_component_at() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    let entity = registry
+        .create_entity((A { _data: 3 }, B { _data: 4 }))
+        .unwrap();
+    let location = registry.entity_location(entity).unwrap();
+    assert_eq!(registry.component_at::<A>(location), Some(&A { _data: 3 }));
+    assert_eq!(registry.component_at::<B>(location), Some(&B { _data: 4 }));
+    assert_eq!(registry.component_at::<C>(location), None);
+    assert_eq!(registry.component_at::<A>((location.0, 2)), None);
+    assert_eq!(
+        registry.component_at::<A>((
+            <C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id(),
+            0
+        )),
+        None
+    );
+}
+
+#[test]
+fn registry_test_stable_archetype_id() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A::default()).unwrap();
+    let second = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry.add_component(first, C::default()).unwrap();
+    let first_id = registry.entity_location(first).unwrap().0;
+    let second_id = registry.entity_location(second).unwrap().0;
+
+    let a = registry
+        .stable_archetype_id(<A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id())
+        .unwrap();
+    let ab = registry.stable_archetype_id(second_id).unwrap();
+    let ac = registry.stable_archetype_id(first_id).unwrap();
+    assert!(a < ab && ab < ac);
+    assert_eq!(registry.archetype_id_of_stable(ab), Some(second_id));
+    assert_eq!(registry.archetype_id_of_stable(ac), Some(first_id));
+    assert_eq!(
+        registry.archetype_id_of_stable(StableArchetypeId::INVALID),
+        None
+    );
+    assert_eq!(
+        registry.stable_archetype_id(<C as ComponentGroup>::DESCRIPTOR.archetype().archetype_id()),
+        None
+    );
+}
+
+#[test]
+fn registry_test_drain_archetype() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let mut registry = Registry::default();
+    let entities = [
+        registry
+            .create_entity((A { _data: 0 }, DropCounted::new(0, &DROPS)))
+            .unwrap(),
+        registry
+            .create_entity((A { _data: 1 }, DropCounted::new(1, &DROPS)))
+            .unwrap(),
+        registry
+            .create_entity((A { _data: 2 }, DropCounted::new(2, &DROPS)))
+            .unwrap(),
+    ];
+    let other = registry.create_entity(A { _data: 3 }).unwrap();
+
+    let mut drain = registry.drain_archetype::<(DropCounted, A)>();
+    assert_eq!(drain.size_hint(), (3, Some(3)));
+    let (entity, (counted, a)) = drain.next().unwrap();
+    assert_eq!(entity, entities[2]);
+    assert_eq!((*counted._data, a._data), (2, 2));
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    drop(counted);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    drop(drain);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+
+    assert!(entities
+        .iter()
+        .all(|entity| !registry.has_component::<A>(*entity)));
+    assert_eq!(registry.get_component::<A>(other), Some(&A { _data: 3 }));
+    assert_eq!(registry.audit_entities().live, 1);
+    assert_eq!(registry.drain_archetype::<(A, DropCounted)>().count(), 0);
+    assert_eq!(registry.drain_archetype::<(A, B)>().count(), 0);
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_component_present_in_any() {
+    let mut registry = Registry::default();
+    assert!(!registry.component_present_in_any::<A>());
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    assert!(registry.component_present_in_any::<A>());
+    assert!(registry.component_present_in_any::<B>());
+    assert!(!registry.component_present_in_any::<C>());
+}
+
+#[test]
+fn registry_test_scoped_commands() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 1 }, B::default()))
+        .unwrap();
+    let second = registry.create_entity(A { _data: 2 }).unwrap();
+    {
+        let commands = registry.scoped_commands();
+        for (entities, components) in commands.iter_entity_components_matching::<A>() {
+            for (entity, a) in entities.iter().zip(components) {
+                if a._data == 1 {
+                    commands.remove_component::<B>(*entity);
+                } else {
+                    commands.add_component(*entity, C { _data: 3 });
+                }
+            }
+        }
+        commands.destroy_entity(first);
+        commands.create_entity(B { _data: 4 });
+        assert_eq!(commands.len(), 4);
+        assert!(commands.has_component::<B>(first));
+    }
+    assert!(registry.entity_location(first).is_none());
+    assert_eq!(registry.get_component::<C>(second), Some(&C { _data: 3 }));
+    assert_eq!(
+        registry
+            .iter_components_matching::<B>()
+            .map(|b| b.len())
+            .sum::<usize>(),
+        1
+    );
+
+    let mut commands = registry.scoped_commands();
+    commands.destroy_entity(second);
+    commands.discard();
+    commands.create_entity(C::default());
+    commands.flush();
+    assert!(commands.is_empty());
+    assert_eq!(
+        commands
+            .iter_components_matching::<C>()
+            .map(|c| c.len())
+            .sum::<usize>(),
+        2
+    );
+    drop(commands);
+    assert!(registry.entity_location(second).is_some());
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_would_change() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A { _data: 1 }, B { _data: 2 }))
+        .unwrap();
+    assert_eq!(registry.would_change(entity, &A { _data: 1 }), Some(false));
+    assert_eq!(registry.would_change(entity, &A { _data: 5 }), Some(true));
+    // B has no equality handler, its PartialEq implementation is used regardless.
+    assert_eq!(registry.would_change(entity, &B { _data: 2 }), Some(false));
+    assert_eq!(registry.would_change(entity, &B { _data: 3 }), Some(true));
+    assert_eq!(registry.would_change(entity, &C::default()), None);
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(registry.would_change(entity, &A { _data: 1 }), None);
+    assert_eq!(registry.get_component::<A>(entity), None);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn registry_test_transition_stats() {
+    let mut registry = Registry::default();
+    let entity = registry.create_entity(A::default()).unwrap();
+    assert!(registry.transition_stats().is_empty());
+    registry.add_component(entity, B::default()).unwrap();
+    let ab = registry.entity_location(entity).unwrap().0;
+    registry.remove_component::<B>(entity).unwrap();
+    let a = registry.entity_location(entity).unwrap().0;
+    for _ in 0..2 {
+        registry.add_component(entity, B::default()).unwrap();
+        registry.remove_component::<B>(entity).unwrap();
+    }
+    registry.add_component(entity, B::default()).unwrap();
+    assert!(registry.add_component(entity, B::default()).is_err());
+
+    let stats = registry.transition_stats();
+    let moves_into = |to| stats.iter().filter(|s| s.1 == to).map(|s| s.2).sum::<u64>();
+    assert_eq!(moves_into(ab), 4);
+    assert_eq!(moves_into(a), 3);
+    assert!(stats.contains(&(ab, a, 3)));
+    assert!(stats
+        .windows(2)
+        .all(|pair| (pair[0].0, pair[0].1) < (pair[1].0, pair[1].1)));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn registry_test_transition_stats_strip_component() {
+    let mut registry = Registry::default();
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap();
+    }
+    let ab = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let a = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    assert_eq!(registry.strip_component::<B>(), 3);
+    assert_eq!(registry.transition_stats(), [(ab, a, 3)]);
+}
+
+#[test]
+fn registry_test_canonical_entity_order() {
+    let mut registry = Registry::default();
+    let entities = [
+        registry.create_entity(A::default()).unwrap(),
+        registry.create_entity(A::default()).unwrap(),
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap(),
+        registry.create_entity(A::default()).unwrap(),
+    ];
+    // Moves the last entity of the archetype into the row of the destroyed one.
+    assert!(registry.destroy_entity(entities[0]));
+    let order = registry.canonical_entity_order();
+    assert_eq!(order.len(), 3);
+    let key = |entity: &Entity| (registry.entity_location(*entity).unwrap().0, entity.index());
+    assert!(order.windows(2).all(|pair| key(&pair[0]) < key(&pair[1])));
+    let single = order.iter().filter(|e| !registry.has_component::<B>(**e));
+    assert!(single
+        .map(Entity::index)
+        .eq([entities[1].index(), entities[3].index()]));
+    assert_eq!(Registry::default().canonical_entity_order(), []);
+}
+
+#[test]
+fn registry_test_column_of() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 4 }, B::default()))
+        .unwrap();
+    registry
+        .create_entity((A { _data: 5 }, B::default()))
+        .unwrap();
+    registry.create_entity(A { _data: 6 }).unwrap();
+    let id = registry.entity_location(first).unwrap().0;
+    assert_eq!(
+        registry.column_of::<A>(id),
+        Some(&[A { _data: 4 }, A { _data: 5 }][..])
+    );
+    assert_eq!(registry.column_of::<B>(id).map(|b| b.len()), Some(2));
+    assert_eq!(registry.column_of::<C>(id), None);
+    assert_eq!(registry.column_of::<A>(ArchetypeId::INVALID), None);
+}
+
+#[test]
+fn registry_test_try_reserve() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.try_reserve::<(A, B)>(5000), Ok(()));
+    let entity = registry
+        .create_entity((A { _data: 7 }, B::default()))
+        .unwrap();
+    let (archetype_id, _) = registry.entity_location(entity).unwrap();
+    let layouts = registry.layout_report();
+    let layout = layouts
+        .iter()
+        .find(|layout| layout.archetype_id == archetype_id)
+        .unwrap();
+    assert!(layout.capacity >= 5000);
+    assert_eq!(registry.get_component::<A>(entity), Some(&A { _data: 7 }));
+
+    // Newly created archetypes still reserve their initial capacity.
+    registry.set_initial_capacity::<(A, C)>(300);
+    assert_eq!(registry.try_reserve::<(A, C)>(1), Ok(()));
+    let id = <(A, C) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert!(registry.archetype_capacity(id).unwrap() >= 300);
+}
+
+#[test]
+fn registry_test_par_reduce() {
+    let mut registry = Registry::default();
+    assert_eq!(
+        registry.par_reduce::<A, _, _, _>(0, |a| a._data, |x, y| x + y),
+        0
+    );
+    for i in 0..3000 {
+        registry.create_entity(A { _data: i }).unwrap();
+        registry
+            .create_entity((A { _data: i }, B { _data: 2 }))
+            .unwrap();
+    }
+    registry.create_entity(C::default()).unwrap();
+    let sum = registry.par_reduce::<A, _, _, _>(0, |a| a._data, |x, y| x + y);
+    assert_eq!(sum, 2 * (0..3000).sum::<usize>());
+    let max = registry.par_reduce::<(B, A), _, _, _>(0, |(b, a)| a._data * b._data, usize::max);
+    assert_eq!(max, 2 * 2999);
+}
+
+#[test]
+fn registry_test_strip_component() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let mut registry = Registry::default();
+    let mut entities = Vec::new();
+    for i in 0..3 {
+        let counted = DropCounted::new(i, &DROPS);
+        entities.push(registry.create_entity((A { _data: i }, counted)).unwrap());
+        let counted = DropCounted::new(i, &DROPS);
+        entities.push(
+            registry
+                .create_entity((A { _data: i }, counted, C::default()))
+                .unwrap(),
+        );
+    }
+    let only_counted = registry.create_entity(DropCounted::new(0, &DROPS)).unwrap();
+    let without_counted = registry.create_entity(A { _data: 9 }).unwrap();
+
+    assert_eq!(registry.strip_component::<DropCounted>(), 7);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 7);
+    // Like remove_component, entities without other components are removed.
+    assert_eq!(registry.entity_location(only_counted), None);
+    for (i, entity) in entities.iter().enumerate() {
+        assert!(!registry.has_component::<DropCounted>(*entity));
+        assert_eq!(
+            registry.get_component::<A>(*entity),
+            Some(&A { _data: i / 2 })
+        );
+        assert_eq!(registry.has_component::<C>(*entity), i % 2 == 1);
+    }
+    assert_eq!(
+        registry.get_component::<A>(without_counted),
+        Some(&A { _data: 9 })
+    );
+    assert_eq!(registry.strip_component::<DropCounted>(), 0);
+    assert_eq!(registry.strip_component::<C>(), 3);
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_add_component_to_matching() {
+    let mut registry = Registry::default();
+    let mut entities = Vec::new();
+    for i in 0..3 {
+        entities.push(
+            registry
+                .create_entity((A { _data: i }, B { _data: 1 }))
+                .unwrap(),
+        );
+        let components = (A { _data: i }, B { _data: 2 }, C::default());
+        entities.push(registry.create_entity(components).unwrap());
+    }
+    let only_a = registry.create_entity(A { _data: 5 }).unwrap();
+
+    let added = registry.add_component_to_matching::<(B, A), C, _>(|(b, a)| C {
+        _data: a._data * 10 + b._data,
+    });
+    assert_eq!(added, 3);
+    for (i, entity) in entities.iter().enumerate() {
+        let expected = if i % 2 == 0 { (i / 2) * 10 + 1 } else { 3 };
+        assert_eq!(
+            registry.get_component::<C>(*entity),
+            Some(&C { _data: expected })
+        );
+        assert_eq!(
+            registry.get_component::<A>(*entity),
+            Some(&A { _data: i / 2 })
+        );
+    }
+    assert!(!registry.has_component::<C>(only_a));
+    assert_eq!(
+        registry.add_component_to_matching::<A, C, _>(|_| C::default()),
+        1
+    );
+    assert!(registry.has_component::<C>(only_a));
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_compact() {
+    let mut registry = Registry::default();
+    let entities = [
+        registry
+            .create_entity((A { _data: 0 }, B::default()))
+            .unwrap(),
+        registry
+            .create_entity((A { _data: 1 }, B::default()))
+            .unwrap(),
+        registry.create_entity(A { _data: 2 }).unwrap(),
+    ];
+    assert!(registry.destroy_entity(entities[0]));
+    let tick = registry.change_tick();
+    // A consistent registry contains no gaps.
+    assert_eq!(registry.compact(), 0);
+    assert_eq!(registry.change_tick(), tick);
+    assert_eq!(
+        registry.get_component::<A>(entities[1]),
+        Some(&A { _data: 1 })
+    );
+    assert_eq!(
+        registry.get_component::<A>(entities[2]),
+        Some(&A { _data: 2 })
+    );
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_query_range() {
+    let mut registry = Registry::default();
+    for i in 0..5 {
+        registry.create_entity(A { _data: i }).unwrap();
+    }
+    for i in 5..10 {
+        registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+    }
+    registry.create_entity(B::default()).unwrap();
+    let all = registry
+        .iter_entity_components_matching::<A>()
+        .flat_map(|(entities, _)| entities.iter().copied())
+        .collect::<Vec<_>>();
+    assert_eq!(all.len(), 10);
+
+    // Windows spanning the boundary between both archetypes.
+    let mut windowed = Vec::new();
+    for skip in (0..12).step_by(3) {
+        windowed.extend(registry.query_range::<A>(skip, 3).map(|(entity, _)| entity));
+    }
+    assert_eq!(windowed, all);
+    let data = registry
+        .query_range::<A>(4, 2)
+        .map(|(_, a)| a._data)
+        .collect::<Vec<_>>();
+    assert_eq!(data.len(), 2);
+    assert!(data.iter().any(|d| *d < 5) && data.iter().any(|d| *d >= 5));
+    assert_eq!(registry.query_range::<A>(10, 5).count(), 0);
+    assert_eq!(registry.query_range::<A>(8, usize::MAX).count(), 2);
+    assert_eq!(registry.query_range::<(A, B)>(0, 2).count(), 2);
+}
+
+#[test]
+fn registry_test_entity_generation() {
+    let mut registry = Registry::default();
+    let entity = registry.create_entity(A::default()).unwrap();
+    assert_eq!(
+        registry.entity_generation(entity.index()),
+        Some(entity.version() as u32)
+    );
+    assert!(registry.destroy_entity(entity));
+    // The slot is free, but its generation already moved on.
+    assert_eq!(
+        registry.entity_generation(entity.index()),
+        Some(entity.version() as u32 + 1)
+    );
+    let reused = registry.create_entity(A::default()).unwrap();
+    assert_eq!(reused.index(), entity.index());
+    assert_eq!(
+        registry.entity_generation(entity.index()),
+        Some(reused.version() as u32)
+    );
+    assert_eq!(registry.entity_generation(entity.index() + 1), None);
+}
+
+#[test]
+fn registry_test_reserve_entities() {
+    let mut registry = Registry::default();
+    registry.create_entity(A::default()).unwrap();
+    registry.reserve_entities(1000);
+    let capacity = registry.entity_capacity();
+    assert!(capacity >= 1001);
+    assert_eq!(registry.spawn_batch_same(A::default(), 1000).len(), 1000);
+    assert_eq!(registry.entity_capacity(), capacity);
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_get_component_mut_each() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A { _data: 1 }).unwrap();
+    let second = registry
+        .create_entity((A { _data: 2 }, B::default()))
+        .unwrap();
+    let without_a = registry.create_entity(B::default()).unwrap();
+    let dead = registry.create_entity(A { _data: 3 }).unwrap();
+    assert!(registry.destroy_entity(dead));
+
+    let mut visited = Vec::new();
+    registry.get_component_mut_each::<A, _>(&[second, dead, first, without_a, second], |e, a| {
+        visited.push(e);
+        a._data *= 10;
+    });
+    assert_eq!(visited, [second, first, second]);
+    assert_eq!(registry.get_component::<A>(first), Some(&A { _data: 10 }));
+    assert_eq!(registry.get_component::<A>(second), Some(&A { _data: 200 }));
+}
+
+#[test]
+fn registry_test_clone_from() {
+    let mut source = Registry::default();
+    let entities = [
+        source.create_entity(A { _data: 1 }).unwrap(),
+        source
+            .create_entity((A { _data: 2 }, B { _data: 3 }))
+            .unwrap(),
+        source
+            .create_entity((B { _data: 4 }, C { _data: 5 }))
+            .unwrap(),
+    ];
+    let destroyed = source.create_entity(C::default()).unwrap();
+    assert!(source.destroy_entity(destroyed));
+
+    let mut registry = Registry::default();
+    registry.try_reserve::<(A, B)>(5000).unwrap();
+    for i in 0..10 {
+        registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+        registry
+            .create_entity((A { _data: i }, C::default()))
+            .unwrap();
+    }
+    registry.clone_from(&source).unwrap();
+    registry.check_invariants();
+    assert_eq!(registry.iter_entities().count(), 3);
+    assert_eq!(
+        registry.get_component::<A>(entities[0]),
+        Some(&A { _data: 1 })
+    );
+    assert_eq!(
+        registry.get_component::<B>(entities[1]),
+        Some(&B { _data: 3 })
+    );
+    assert_eq!(
+        registry.get_component::<C>(entities[2]),
+        Some(&C { _data: 5 })
+    );
+    assert_eq!(
+        registry.canonical_entity_order(),
+        source.canonical_entity_order()
+    );
+    // The archetype existing in both registries keeps its memory.
+    let (archetype_id, _) = registry.entity_location(entities[1]).unwrap();
+    let layouts = registry.layout_report();
+    let layout = layouts
+        .iter()
+        .find(|layout| layout.archetype_id == archetype_id)
+        .unwrap();
+    assert!(layout.capacity >= 5000);
+    let entity = registry.create_entity(C::default()).unwrap();
+    assert_eq!(source.create_entity(C::default()).unwrap(), entity);
+
+    source.create_entity(DropLogA::default()).unwrap();
+    assert_eq!(registry.clone_from(&source), Err(DropLogA::ID));
+    assert_eq!(registry.iter_entities().count(), 4);
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_archetypes_with() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.archetypes_with::<A>().count(), 0);
+    let entities = [
+        registry.create_entity(A::default()).unwrap(),
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap(),
+        registry
+            .create_entity((B::default(), C::default()))
+            .unwrap(),
+    ];
+    let ids = entities.map(|entity| registry.entity_location(entity).unwrap().0);
+    let mut with_a = registry.archetypes_with::<A>().collect::<Vec<_>>();
+    with_a.sort();
+    let mut expected = [ids[0], ids[1]];
+    expected.sort();
+    assert_eq!(with_a, expected);
+    assert_eq!(
+        registry.archetypes_with::<C>().collect::<Vec<_>>(),
+        [ids[2]]
+    );
+    assert!(registry.destroy_entity(entities[2]));
+    assert_eq!(registry.archetypes_with::<C>().count(), 1);
+}
+
+#[test]
+fn registry_test_spawn_contiguous() {
+    let mut registry = Registry::default();
+    registry
+        .create_entity((A { _data: 0 }, B::default()))
+        .unwrap();
+    let first = registry.spawn_contiguous((1..4).map(|i| (A { _data: i }, B::default())));
+    registry
+        .create_entity((A { _data: 9 }, B::default()))
+        .unwrap();
+    let second = registry.spawn_contiguous((4..6).map(|i| (B::default(), A { _data: i })));
+    assert_eq!(first, 1..4);
+    assert_eq!(second, 5..7);
+
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let (archetype_id, _) = registry.entity_location(entity).unwrap();
+    let column = registry.column_of::<A>(archetype_id).unwrap();
+    let data = |range: Range<u32>| column[range.start as usize..range.end as usize].to_vec();
+    assert_eq!(
+        data(first),
+        [A { _data: 1 }, A { _data: 2 }, A { _data: 3 }]
+    );
+    assert_eq!(data(second), [A { _data: 4 }, A { _data: 5 }]);
+    assert_eq!(registry.spawn_contiguous(core::iter::empty::<A>()), 0..0);
+}
+
+#[test]
+fn registry_test_runtime_and_static_archetypes_match() {
+    let mut registry = Registry::default();
+    let created = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let single = registry.create_entity(A::default()).unwrap();
+    let a = registry.entity_location(single).unwrap().0;
+    registry.add_component(single, B::default()).unwrap();
+    let ab = registry.entity_location(created).unwrap().0;
+    assert_eq!(registry.entity_location(single), Some((ab, 1)));
+    registry.remove_component::<B>(single).unwrap();
+    assert_eq!(registry.entity_location(single).unwrap().0, a);
+    assert_eq!(registry.archetype_shapes().len(), 2);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn registry_test_par_query_chunked() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut registry = Registry::default();
+    for i in 0..1000 {
+        registry.create_entity(A { _data: i }).unwrap();
+    }
+    for i in 0..10 {
+        registry
+            .create_entity((A { _data: i }, B::default()))
+            .unwrap();
+    }
+    let chunks = AtomicUsize::new(0);
+    let entities = AtomicUsize::new(0);
+    let sum = AtomicUsize::new(0);
+    registry.par_query_chunked::<A, _>(64, |chunk_entities, a| {
+        assert_eq!(chunk_entities.len(), a.len());
+        assert!(a.len() <= 64);
+        chunks.fetch_add(1, Ordering::Relaxed);
+        entities.fetch_add(a.len(), Ordering::Relaxed);
+        sum.fetch_add(a.iter().map(|a| a._data).sum::<usize>(), Ordering::Relaxed);
+    });
+    assert_eq!(chunks.load(Ordering::Relaxed), 16 + 1);
+    assert_eq!(entities.load(Ordering::Relaxed), 1010);
+    assert_eq!(
+        sum.load(Ordering::Relaxed),
+        (0..1000).sum::<usize>() + (0..10).sum::<usize>()
+    );
+    registry.par_query_chunked::<(A, B), _>(0, |_, (a, b)| assert_eq!((a.len(), b.len()), (1, 1)));
+}
+
+#[test]
+fn registry_test_co_occurrence() {
+    let mut registry = Registry::default();
+    assert!(registry.co_occurrence().is_empty());
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), B::default()))
+            .unwrap();
+    }
+    registry
+        .create_entity((A::default(), B::default(), C::default()))
+        .unwrap();
+    registry.create_entity(A::default()).unwrap();
+    let empty = registry
+        .create_entity((B::default(), C::default()))
+        .unwrap();
+    assert!(registry.destroy_entity(empty));
+    assert_eq!(
+        registry.co_occurrence(),
+        [(A::ID, B::ID, 4), (A::ID, C::ID, 1), (B::ID, C::ID, 1)]
+    );
+}
+
+#[test]
+fn registry_test_prewarm() {
+    let mut registry = Registry::default();
+    registry.prewarm::<(A, B)>(1000);
+    registry.prewarm::<C>(0);
+    assert_eq!(registry.iter_entities().count(), 0);
+    let layouts = registry.layout_report();
+    assert_eq!(layouts.len(), 2);
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    let layout = layouts
+        .iter()
+        .find(|layout| layout.archetype_id == id)
+        .unwrap();
+    assert!(layout.capacity >= 1000);
+    assert_eq!(layout.entity_count, 0);
+
+    let entity = registry
+        .create_entity((B::default(), A::default()))
+        .unwrap();
+    assert_eq!(registry.entity_location(entity), Some((id, 0)));
+    assert_eq!(registry.layout_report().len(), 2);
+}
+
+#[test]
+fn registry_test_contains_fast() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A::default(), C::default()))
+        .unwrap();
+    assert!(registry.contains_fast::<A>(entity));
+    assert!(!registry.contains_fast::<B>(entity));
+    assert!(registry.contains_fast::<C>(entity));
+    assert!(registry.destroy_entity(entity));
+    assert!(!registry.contains_fast::<A>(entity));
+}
+
+#[test]
+fn registry_test_entity_ref() {
+    let mut registry = Registry::default();
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let other = registry.create_entity(C::default()).unwrap();
+
+    let entity_ref = registry.entity_ref(entity).unwrap();
+    assert_eq!(entity_ref.entity(), entity);
+    assert_eq!(
+        entity_ref.archetype_id(),
+        <(A, B) as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .archetype_id()
+    );
+    assert_eq!(entity_ref.component_count(), 2);
+    assert!(entity_ref.contains::<A>());
+    assert!(!entity_ref.contains::<C>());
+    assert!(entity_ref.get::<B>().is_some());
+    assert!(entity_ref.get::<C>().is_none());
+
+    let tick = registry.change_tick();
+    let mut entity_ref = registry.entity_ref_mut(other).unwrap();
+    assert_eq!(entity_ref.component_count(), 1);
+    assert!(entity_ref.get_mut::<A>().is_none());
+    *entity_ref.get_mut::<C>().unwrap() = C::default();
+    assert!(entity_ref.get::<C>().is_some());
+    assert!(registry.change_tick() > tick);
+
+    assert!(registry.destroy_entity(other));
+    assert!(registry.entity_ref(other).is_none());
+    assert!(registry.entity_ref_mut(other).is_none());
+}
+
+#[test]
+fn registry_test_swap_rows() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A { _data: 1 }).unwrap();
+    let second = registry.create_entity(A { _data: 2 }).unwrap();
+    let third = registry.create_entity(A { _data: 3 }).unwrap();
+    let other = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+
+    assert!(registry.swap_rows(first, third));
+    assert!(registry.swap_rows(second, second));
+    let (entities, components) = registry.iter_entity_components_exact::<A>();
+    assert_eq!(entities, &[third, second, first]);
+    let values = components.iter().map(|a| a._data).collect::<Vec<_>>();
+    assert_eq!(values, [3, 2, 1]);
+    assert_eq!(registry.get_component::<A>(first).unwrap()._data, 1);
+    assert_eq!(registry.get_component::<A>(third).unwrap()._data, 3);
+
+    assert!(!registry.swap_rows(first, other));
+    assert!(registry.destroy_entity(second));
+    assert!(!registry.swap_rows(first, second));
+    assert!(registry.destroy_entity(third));
+    assert_eq!(registry.get_component::<A>(first).unwrap()._data, 1);
+}
+
+#[test]
+fn registry_test_is_archetype_empty() {
+    let mut registry = Registry::default();
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.is_archetype_empty(id), None);
+    let entity = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    assert_eq!(registry.is_archetype_empty(id), Some(false));
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(registry.is_archetype_empty(id), Some(true));
+}
+
+#[test]
+fn registry_test_replace_with() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let mut registry = Registry::default();
+    for _ in 0..3 {
+        registry
+            .create_entity((A::default(), DropCounted::new(0, &DROPS)))
+            .unwrap();
+    }
+    let old = registry.iter_entities().collect::<Vec<_>>();
+    assert!(registry.destroy_entity(old[2]));
+    let mut other = Registry::default();
+    let entity = other.create_entity(B { _data: 1 }).unwrap();
+    other.create_entity(B { _data: 2 }).unwrap();
+    other.create_entity(B { _data: 3 }).unwrap();
+    other.create_entity(B { _data: 4 }).unwrap();
+
+    registry.replace_with(other);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+    assert_eq!(registry.iter_entities().count(), 4);
+    assert!(registry.iter_single::<A>().next().is_none());
+    // Old handles, also of destroyed entities, do not refer to entities of other.
+    for stale in old.iter().copied().chain([entity]) {
+        assert_eq!(registry.entity_location(stale), None);
+        assert!(!registry.has_component::<B>(stale));
+    }
+    let mut moved = entity;
+    moved.set_version(registry.entity_generation(entity.index()).unwrap() as u8);
+    assert_eq!(registry.get_component::<B>(moved), Some(&B { _data: 1 }));
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_archetype_capacity() {
+    let mut registry = Registry::default();
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.archetype_capacity(id), None);
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry.prewarm::<(A, B)>(101);
+    let capacity = registry.archetype_capacity(id).unwrap();
+    assert!(capacity >= 101);
+    assert!(capacity >= registry.len_of_archetype(id).unwrap());
+}
+
+#[test]
+fn registry_test_for_each_metadata() {
+    let mut registry = Registry::default();
+    let first = registry.create_entity(A::default()).unwrap();
+    let second = registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    let third = registry.create_entity(A::default()).unwrap();
+
+    let mut metadata = Vec::new();
+    registry
+        .for_each_metadata(|archetype_id, row, entity| metadata.push((archetype_id, row, entity)));
+    let a_id = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let ab_id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(metadata.len(), 3);
+    assert!(metadata.contains(&(a_id, 0, first)));
+    assert!(metadata.contains(&(a_id, 1, third)));
+    assert!(metadata.contains(&(ab_id, 0, second)));
+}
+
+#[test]
+fn registry_test_reserve_from_histogram() {
+    let mut registry = Registry::default();
+    let a_id = <A as ComponentGroup>::DESCRIPTOR.archetype().archetype_id();
+    let ab_id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    registry.prewarm::<A>(0);
+    assert_eq!(
+        registry.reserve_from_histogram(&[(a_id, 500), (ab_id, 10)]),
+        Err(ab_id)
+    );
+    assert!(registry.archetype_capacity(a_id).unwrap() < 500);
+
+    registry.prewarm::<(A, B)>(0);
+    assert_eq!(
+        registry.reserve_from_histogram(&[(a_id, 500), (ab_id, 1000)]),
+        Ok(())
+    );
+    assert!(registry.archetype_capacity(a_id).unwrap() >= 500);
+    assert!(registry.archetype_capacity(ab_id).unwrap() >= 1000);
+}
+
+#[test]
+fn registry_test_extract_components() {
+    let mut registry = Registry::default();
+    let first = registry
+        .create_entity((A { _data: 1 }, B::default()))
+        .unwrap();
+    let second = registry
+        .create_entity((A { _data: 2 }, C::default()))
+        .unwrap();
+    let third = registry
+        .create_entity((A { _data: 3 }, B::default()))
+        .unwrap();
+    let single = registry.create_entity(A { _data: 4 }).unwrap();
+
+    let mut extracted = registry
+        .extract_components::<A>()
+        .into_iter()
+        .map(|(entity, a)| (entity, a._data))
+        .collect::<Vec<_>>();
+    extracted.sort_by_key(|&(_, data)| data);
+    assert_eq!(
+        extracted,
+        [(first, 1), (second, 2), (third, 3), (single, 4)]
+    );
+
+    assert!(!registry.has_component::<A>(first));
+    assert!(registry.has_component::<B>(first));
+    assert!(!registry.has_component::<A>(second));
+    assert!(registry.has_component::<C>(second));
+    assert!(registry.has_component::<B>(third));
+    // Like remove_component, entities without other components are removed.
+    assert_eq!(registry.entity_location(single), None);
+    assert!(registry.extract_components::<A>().is_empty());
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_count_archetypes_matching() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.count_archetypes_matching::<A>(), 0);
+    registry.create_entity(A::default()).unwrap();
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((A::default(), B::default(), C::default()))
+        .unwrap();
+    let entity = registry.create_entity(C::default()).unwrap();
+    assert!(registry.destroy_entity(entity));
+
+    assert_eq!(registry.count_archetypes_matching::<A>(), 3);
+    assert_eq!(registry.count_archetypes_matching::<(B, A)>(), 2);
+    assert_eq!(registry.count_archetypes_matching::<C>(), 2);
+}
+
+#[test]
+fn registry_test_try_get_component() {
+    let mut registry = Registry::default();
+    let entity = registry.create_entity(A { _data: 7 }).unwrap();
+    assert_eq!(registry.try_get_component::<A>(entity).unwrap()._data, 7);
+    assert_eq!(
+        registry.try_get_component::<B>(entity).unwrap_err(),
+        AccessError::ComponentNotPresent
+    );
+    assert_eq!(
+        registry
+            .try_get_component::<A>(Entity::INVALID)
+            .unwrap_err(),
+        AccessError::NullEntity
+    );
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(
+        registry.try_get_component::<A>(entity).unwrap_err(),
+        AccessError::DeadEntity
+    );
+}
+
+#[test]
+fn registry_test_entity_count_with_component() {
+    let mut registry = Registry::default();
+    assert_eq!(registry.entity_count_with_component::<A>(), 0);
+    registry.create_entity(A::default()).unwrap();
+    registry
+        .create_entity((A::default(), B::default()))
+        .unwrap();
+    registry
+        .create_entity((B::default(), C::default()))
+        .unwrap();
+    let entity = registry
+        .create_entity((A::default(), C::default()))
+        .unwrap();
+    assert_eq!(registry.entity_count_with_component::<A>(), 3);
+    assert_eq!(registry.entity_count_with_component::<B>(), 2);
+    assert!(registry.destroy_entity(entity));
+    assert_eq!(registry.entity_count_with_component::<A>(), 2);
+    assert_eq!(registry.entity_count_with_component::<C>(), 1);
+}
+
+#[test]
+fn registry_test_spawn() {
+    let mut registry = Registry::default();
+    let entity = registry.spawn((A { _data: 3 }, B::default()));
+    let (a, _) = registry.get_components::<(A, B)>(entity).unwrap();
+    assert_eq!(a._data, 3);
+    assert_eq!(registry.iter_components_matching::<(A, B)>().count(), 1);
+}
+
+#[test]
+fn registry_test_despawn() {
+    let mut registry = Registry::default();
+    let first = registry.spawn((A { _data: 1 }, B { _data: 10 }));
+    let second = registry.spawn((A { _data: 2 }, B { _data: 20 }));
+    let third = registry.spawn((A { _data: 3 }, B { _data: 30 }));
+
+    assert!(registry.despawn(second));
+    assert!(!registry.despawn(second));
+    assert!(registry.get_component::<A>(second).is_none());
+    let (a, b) = registry.get_components::<(A, B)>(first).unwrap();
+    assert_eq!((a._data, b._data), (1, 10));
+    let (a, b) = registry.get_components::<(A, B)>(third).unwrap();
+    assert_eq!((a._data, b._data), (3, 30));
+
+    assert!(registry.despawn(third));
+    assert_eq!(registry.get_component::<A>(first).unwrap()._data, 1);
+}
+
+#[test]
+fn registry_test_add_component_roundtrip() {
+    let mut registry = Registry::default();
+    let first = registry.spawn((A { _data: 1 }, B { _data: 10 }));
+    let second = registry.spawn((A { _data: 2 }, B { _data: 20 }));
+    let third = registry.spawn((A { _data: 3 }, B { _data: 30 }));
+
+    registry.add_component(first, C { _data: 100 }).unwrap();
+    let (a, b, c) = registry.get_components::<(A, B, C)>(first).unwrap();
+    assert_eq!((a._data, b._data, c._data), (1, 10, 100));
+    // The entity swapped into the vacated row must still be found.
+    let (a, b) = registry.get_components::<(A, B)>(third).unwrap();
+    assert_eq!((a._data, b._data), (3, 30));
+
+    let rejected = registry.add_component(second, A { _data: 5 }).unwrap_err();
+    assert_eq!(rejected, AddComponentError::AlreadyPresent(A { _data: 5 }));
+    assert_eq!(rejected.into_component()._data, 5);
+    let (a, b) = registry.get_components::<(A, B)>(second).unwrap();
+    assert_eq!((a._data, b._data), (2, 20));
+    assert!(!registry.has_component::<C>(second));
+
+    assert!(registry.despawn(second));
+    assert_eq!(
+        registry.add_component(second, C { _data: 6 }),
+        Err(AddComponentError::InvalidEntity(C { _data: 6 }))
+    );
+}
+
+#[test]
+fn registry_test_remove_component_errors() {
+    let mut registry = Registry::default();
+    let entity = registry.spawn((A { _data: 1 }, B { _data: 2 }, C { _data: 3 }));
+    let other = registry.spawn((A { _data: 4 }, B { _data: 5 }, C { _data: 6 }));
+    let single = registry.spawn(C { _data: 7 });
+    let remaining = registry.spawn(C { _data: 8 });
+
+    // The (A, C) archetype does not exist yet and is created on the fly.
+    let id = <(A, C) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), None);
+    assert_eq!(registry.remove_component::<B>(entity).unwrap()._data, 2);
+    assert_eq!(registry.len_of_archetype(id), Some(1));
+    let (a, c) = registry.get_components::<(A, C)>(entity).unwrap();
+    assert_eq!((a._data, c._data), (1, 3));
+    let (a, b, c) = registry.get_components::<(A, B, C)>(other).unwrap();
+    assert_eq!((a._data, b._data, c._data), (4, 5, 6));
+
+    assert_eq!(
+        registry.remove_component::<B>(entity).unwrap_err(),
+        RemoveComponentError::MissingComponent
+    );
+
+    // Removing the last component removes the entity.
+    assert_eq!(registry.remove_component::<C>(single).unwrap()._data, 7);
+    assert!(registry.entity_location(single).is_none());
+    assert_eq!(
+        registry.remove_component::<C>(single).unwrap_err(),
+        RemoveComponentError::InvalidEntity
+    );
+    assert_eq!(registry.get_component::<C>(remaining).unwrap()._data, 8);
+}
+
+#[test]
+fn registry_test_get_and_get_mut() {
+    let mut registry = Registry::default();
+    let entity = registry.spawn((A { _data: 1 }, B { _data: 2 }));
+    assert_eq!(registry.get::<A>(entity).unwrap()._data, 1);
+    registry.get_mut::<B>(entity).unwrap()._data = 20;
+    assert_eq!(registry.get::<B>(entity).unwrap()._data, 20);
+    assert!(registry.get::<C>(entity).is_none());
+    assert!(registry.get_mut::<C>(entity).is_none());
+
+    assert!(registry.despawn(entity));
+    assert!(registry.get::<A>(entity).is_none());
+    assert!(registry.get_mut::<B>(entity).is_none());
+}
+
+#[test]
+fn registry_test_drop_runs_component_destructors_once() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let mut registry = Registry::default();
+    let mut entities = Vec::new();
+    for index in 0..300 {
+        entities.push(registry.spawn((DropCounted::new(index, &DROPS), A::default())));
+    }
+    for entity in &entities[..100] {
+        registry.add_component(*entity, B::default()).unwrap();
+    }
+    registry.spawn(DropCounted::new(0, &DROPS));
+    assert!(registry.despawn(entities[150]));
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+    drop(registry);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 301);
+}
+
+#[test]
+fn registry_test_spawn_and_query_16_components() {
+    type Wide = (
+        W1,
+        W2,
+        W3,
+        W4,
+        W5,
+        W6,
+        W7,
+        W8,
+        W9,
+        W10,
+        W11,
+        W12,
+        W13,
+        W14,
+        W15,
+        W16,
+    );
+    let mut registry = Registry::default();
+    let components = (
+        W1(1),
+        W2(2),
+        W3(3),
+        W4(4),
+        W5(5),
+        W6(6),
+        W7(7),
+        W8(8),
+        W9(9),
+        W10(10),
+        W11(11),
+        W12(12),
+        W13(13),
+        W14(14),
+        W15(15),
+        W16(16),
+    );
+    let entity = registry.spawn(components);
+    let other = registry.spawn((W9(90), W16(160)));
+    assert_eq!(
+        <Wide as ComponentGroup>::DESCRIPTOR
+            .archetype()
+            .archetype_id()
+            .component_count(),
+        16
+    );
+    assert!(registry.has_component::<W16>(entity));
+    assert_eq!(registry.get::<W16>(entity), Some(&W16(16)));
+    assert_eq!(registry.get::<W1>(entity), Some(&W1(1)));
+
+    let mut values = Vec::new();
+    for (w9, w16) in registry.iter_components_matching::<(W9, W16)>() {
+        for (w9, w16) in w9.iter().zip(w16) {
+            values.push((w9.0, w16.0));
+        }
+    }
+    values.sort();
+    assert_eq!(values, [(9, 16), (90, 160)]);
+
+    assert_eq!(registry.remove_component::<W8>(entity), Ok(W8(8)));
+    registry.add_component(entity, W8(80)).unwrap();
+    assert_eq!(registry.get::<W8>(entity), Some(&W8(80)));
+    assert_eq!(registry.get::<W15>(entity), Some(&W15(15)));
+    assert!(registry.despawn(entity));
+    assert_eq!(registry.get::<W16>(other), Some(&W16(160)));
+}
+
+#[test]
+fn registry_test_compact_holes() {
+    let mut registry = Registry::default();
+    let entities = (0..5)
+        .map(|i| {
+            registry
+                .create_entity((A { _data: i }, B { _data: i * 10 }))
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let other = registry.create_entity(A { _data: 9 }).unwrap();
+    assert_eq!(registry.compact(), 0);
+
+    // Leave holes by removing entities without touching their rows, like a partially written state.
+    assert!(registry.entities.destroy_entity(entities[0]));
+    assert!(registry.entities.destroy_entity(entities[3]));
+    assert_eq!(registry.compact(), 2);
+    assert_eq!(registry.compact(), 0);
+
+    let id = <(A, B) as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), Some(3));
+    for i in [1, 2, 4] {
+        let entity = entities[i];
+        assert!(registry.entity_location(entity).unwrap().1 < 3);
+        assert_eq!(
+            registry.get_components::<(A, B)>(entity),
+            Some((&A { _data: i }, &B { _data: i * 10 }))
+        );
+    }
+    assert_eq!(registry.get_component::<A>(other), Some(&A { _data: 9 }));
+    registry.check_invariants();
+}
+
+#[test]
+fn registry_test_full_archetype_rejects_entities() {
+    use crate::descriptors::component_descriptor::ComponentDescriptor;
+
+    struct Bundle([ComponentDescriptor; 1]);
+    unsafe impl DynBundle for Bundle {
+        fn descriptors(&self) -> &[ComponentDescriptor] {
+            &self.0
+        }
+
+        unsafe fn write_components(&self, destinations: &[*mut u8]) {
+            core::ptr::write(destinations[0] as *mut W1, W1(1));
+        }
+    }
+
+    let mut registry = Registry::default();
+    let limit = MAX_ENTITIES_PER_ARCHETYPE - 1;
+    let entities = registry.spawn_batch_same(W1(0), MAX_ENTITIES_PER_ARCHETYPE);
+    assert_eq!(entities.len() as u32, limit);
+    let entity = registry.create_entity((W1(2), W2(2))).unwrap();
+
+    assert!(registry.create_entity(W1(1)).is_err());
+    assert!(registry.spawn_mut::<W1, W1>(W1(1)).is_err());
+    assert!(registry
+        .spawn_dynamic(&Bundle([<W1 as Component>::DESCRIPTOR]))
+        .is_none());
+    assert!(registry.spawn_batch_same(W1(1), 1).is_empty());
+    assert_eq!(registry.spawn_contiguous([W1(1)]), limit..limit);
+    assert_eq!(
+        registry.remove_component::<W2>(entity),
+        Err(RemoveComponentError::LimitExceeded)
+    );
+    assert_eq!(
+        registry.get_components::<(W1, W2)>(entity),
+        Some((&W1(2), &W2(2)))
+    );
+    let id = <W1 as ComponentGroup>::DESCRIPTOR
+        .archetype()
+        .archetype_id();
+    assert_eq!(registry.len_of_archetype(id), Some(limit));
+}
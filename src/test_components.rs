@@ -1,4 +1,5 @@
 use crate::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct A {
@@ -12,6 +13,10 @@ impl Default for A {
 impl Component for A {
     const NAME: &'static str = "A";
     const ID: ComponentTypeId = ComponentTypeId::from_u16(1);
+    const CLONE_HANDLER: Option<unsafe fn(*const u8, *mut u8, usize)> =
+        Some(ComponentDescriptor::clone_handler_wrapper::<Self>);
+    const EQ_HANDLER: Option<unsafe fn(*const u8, *const u8) -> bool> =
+        Some(ComponentDescriptor::eq_handler_wrapper::<Self>);
 }
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct B {
@@ -25,6 +30,8 @@ impl Default for B {
 impl Component for B {
     const NAME: &'static str = "B";
     const ID: ComponentTypeId = ComponentTypeId::from_u16(2);
+    const CLONE_HANDLER: Option<unsafe fn(*const u8, *mut u8, usize)> =
+        Some(ComponentDescriptor::clone_handler_wrapper::<Self>);
 }
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct C {
@@ -38,6 +45,8 @@ impl Default for C {
 impl Component for C {
     const NAME: &'static str = "C";
     const ID: ComponentTypeId = ComponentTypeId::from_u16(3);
+    const CLONE_HANDLER: Option<unsafe fn(*const u8, *mut u8, usize)> =
+        Some(ComponentDescriptor::clone_handler_wrapper::<Self>);
 }
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct DropLogA {
@@ -99,3 +108,60 @@ impl Drop for DropLogC {
         std::println!("Dropping C: {:#?}", self as *const Self);
     }
 }
+/// Counts its drops in the counter it is created with, each test declares its own counter.
+/// The payload is boxed, so dropping it twice is caught as well.
+#[derive(Debug)]
+pub struct DropCounted {
+    pub _data: alloc::boxed::Box<usize>,
+    drops: &'static AtomicUsize,
+}
+impl DropCounted {
+    pub fn new(data: usize, drops: &'static AtomicUsize) -> Self {
+        Self {
+            _data: alloc::boxed::Box::new(data),
+            drops,
+        }
+    }
+}
+impl Component for DropCounted {
+    const NAME: &'static str = "DropCounted";
+    const ID: ComponentTypeId = ComponentTypeId::from_u16(7);
+}
+impl Drop for DropCounted {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+macro_rules! wide_components {
+    ($(($name:ident, $id:expr)),*) => {
+        $(
+            #[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+            pub struct $name(pub u32);
+            impl Component for $name {
+                const NAME: &'static str = stringify!($name);
+                const ID: ComponentTypeId = ComponentTypeId::from_u16($id);
+            }
+        )*
+    };
+}
+
+// Components for testing groups of up to MAX_COMPONENTS_PER_ENTITY components.
+wide_components!(
+    (W1, 101),
+    (W2, 102),
+    (W3, 103),
+    (W4, 104),
+    (W5, 105),
+    (W6, 106),
+    (W7, 107),
+    (W8, 108),
+    (W9, 109),
+    (W10, 110),
+    (W11, 111),
+    (W12, 112),
+    (W13, 113),
+    (W14, 114),
+    (W15, 115),
+    (W16, 116)
+);
@@ -0,0 +1,25 @@
+/// A point in time of a registry, used for detecting changes.
+/// Ticks increase with every change made to a registry.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tick(u64);
+
+impl Tick {
+    /// The tick before any change has been made.
+    pub const ZERO: Tick = Tick(0);
+
+    /// Creates a tick from its raw value.
+    pub const fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value of the tick.
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Advances the tick and returns the new value.
+    pub(crate) fn increment(&mut self) -> Tick {
+        self.0 += 1;
+        *self
+    }
+}
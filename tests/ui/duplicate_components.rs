@@ -0,0 +1,12 @@
+use shard_ecs::*;
+
+struct Position;
+impl Component for Position {
+    const NAME: &'static str = "Position";
+}
+
+const DESCRIPTOR: &ComponentGroupDescriptor = &<(Position, Position) as ComponentGroup>::DESCRIPTOR;
+
+fn main() {
+    let _ = DESCRIPTOR;
+}
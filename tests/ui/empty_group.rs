@@ -0,0 +1,7 @@
+use shard_ecs::*;
+
+const DESCRIPTOR: ComponentGroupDescriptor = ComponentGroupDescriptor::new(&[]);
+
+fn main() {
+    let _ = DESCRIPTOR;
+}
@@ -0,0 +1,13 @@
+use shard_ecs::*;
+
+struct Position;
+impl Component for Position {
+    const NAME: &'static str = "Position";
+    const COLUMN_ALIGN: usize = 48;
+}
+
+const DESCRIPTOR: &ComponentDescriptor = &<Position as Component>::DESCRIPTOR;
+
+fn main() {
+    let _ = DESCRIPTOR;
+}
@@ -0,0 +1,14 @@
+use shard_ecs::*;
+
+struct Position;
+impl Component for Position {
+    const NAME: &'static str = "Position";
+}
+
+const DESCRIPTOR: ComponentGroupDescriptor = ComponentGroupDescriptor::new(
+    &[<Position as Component>::DESCRIPTOR; MAX_COMPONENTS_PER_ENTITY + 1],
+);
+
+fn main() {
+    let _ = DESCRIPTOR;
+}